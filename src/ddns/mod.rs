@@ -0,0 +1,682 @@
+//! DNS updates for DHCP leases
+//!
+//! Builds the DNS UPDATE ([RFC 2136]) messages a DHCP server sends to
+//! publish a lease's A/AAAA & PTR records, using DHCID records ([RFC 4701])
+//! to resolve conflicts between clients as described in [RFC 4703]. The
+//! messages are only built, sending them (and any TSIG signing) is left to
+//! the caller. Requires the `ddns` feature.
+//!
+//! Adding the forward mapping for a lease takes up to two updates:
+//!
+//! 1. [`DdnsLease::add_forward`] adds the address & DHCID, only if the name is not in use
+//! 2. if that fails with `YXDOMAIN`, [`DdnsLease::replace_forward`] replaces
+//!    the address only if the name's DHCID matches the client. If it fails with
+//!    `NXRRSET` the name belongs to another client and must be left alone
+//!
+//! Removing a lease is the reverse, [`DdnsLease::remove_forward`] followed by
+//! [`DdnsLease::remove_dhcid`] once no addresses are left.
+//!
+//! ```rust
+//! use dhcproto::{ddns::{DdnsLease, DhcidSource}, v4::HType, Name};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let source = DhcidSource::Chaddr {
+//!     htype: HType::Eth,
+//!     chaddr: vec![1, 2, 3, 4, 5, 6],
+//! };
+//! let fqdn = Name::from_ascii("client.example.com.")?;
+//! let lease = DdnsLease::new(&source, fqdn, "192.0.2.10".parse()?, 3600);
+//! assert_eq!(
+//!     lease.dhcid().to_string(),
+//!     "AAABxLmlskllE0MVjd57zHcWmEH3pCQ6VytcKD//7es/deY="
+//! );
+//! // send to the primary for example.com
+//! let update = lease.add_forward(&Name::from_ascii("example.com.")?);
+//! let bytes = update.to_vec()?;
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 2136]: https://datatracker.ietf.org/doc/html/rfc2136
+//! [RFC 4701]: https://datatracker.ietf.org/doc/html/rfc4701
+//! [RFC 4703]: https://datatracker.ietf.org/doc/html/rfc4703
+use std::{fmt, net::IpAddr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use trust_dns_proto::{
+    op::{Message, MessageType, OpCode, Query},
+    rr::{
+        rdata::NULL,
+        {DNSClass, Name, RData, Record, RecordType},
+    },
+};
+
+use crate::{
+    v4::{self, client_id::ClientId, HType},
+    v6::{self, duid::Duid},
+};
+
+/// DHCID resource record type code. trust-dns does not know about DHCID,
+/// so it is carried as [`RecordType::Unknown`]
+pub const DHCID_TYPE: u16 = 49;
+
+/// DHCID record type
+pub const DHCID: RecordType = RecordType::Unknown(DHCID_TYPE);
+
+/// SHA-256, the only digest type defined for DHCID
+pub const DIGEST_SHA256: u8 = 1;
+
+// length of a SHA-256 digest
+const SHA256_LEN: usize = 32;
+
+/// The client identity a DHCID is computed from
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DhcidSource {
+    /// 0x0000 hardware type & chaddr of a DHCPv4 client
+    Chaddr {
+        /// hardware type
+        htype: HType,
+        /// hardware address (`hlen` bytes of chaddr)
+        chaddr: Vec<u8>,
+    },
+    /// 0x0001 data of a DHCPv4 client identifier option (including the type byte)
+    ClientId(Vec<u8>),
+    /// 0x0002 DUID of a DHCPv6 client or an RFC 4361 DHCPv4 client
+    Duid(Duid),
+}
+
+impl DhcidSource {
+    /// the identifier type code
+    pub fn identifier_type(&self) -> u16 {
+        match self {
+            DhcidSource::Chaddr { .. } => 0,
+            DhcidSource::ClientId(_) => 1,
+            DhcidSource::Duid(_) => 2,
+        }
+    }
+    /// pick the identifier for a DHCPv4 client. Per RFC 4701 the DUID of
+    /// an RFC 4361 client identifier is used if present, then the client
+    /// identifier, then chaddr
+    pub fn from_v4(msg: &v4::Message) -> Self {
+        match msg.opts().client_id() {
+            Some(ClientId::Node { duid, .. }) => DhcidSource::Duid(duid),
+            Some(id) => DhcidSource::ClientId(id.to_vec()),
+            None => DhcidSource::Chaddr {
+                htype: msg.htype(),
+                chaddr: msg.chaddr().to_vec(),
+            },
+        }
+    }
+    /// the DUID of a DHCPv6 client, from its ClientId option
+    pub fn from_v6(msg: &v6::Message) -> Option<Self> {
+        match msg.opts().get(v6::OptionCode::ClientId)? {
+            v6::DhcpOption::ClientId(duid) => Some(DhcidSource::Duid(duid.clone().into())),
+            _ => None,
+        }
+    }
+    fn identifier(&self) -> Vec<u8> {
+        match self {
+            DhcidSource::Chaddr { htype, chaddr } => {
+                let mut id = vec![(*htype).into()];
+                id.extend_from_slice(chaddr);
+                id
+            }
+            DhcidSource::ClientId(id) => id.clone(),
+            DhcidSource::Duid(duid) => duid.as_ref().to_vec(),
+        }
+    }
+}
+
+/// DHCID RDATA: identifier type, digest type & digest
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "Vec<u8>")
+)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Dhcid(Vec<u8>);
+
+/// DHCID RDATA without an identifier type, digest type & digest, or with a
+/// SHA-256 digest that isn't 32 bytes
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+#[error("DHCID RDATA has the wrong length, {0} bytes")]
+pub struct DhcidLenError(pub usize);
+
+impl TryFrom<Vec<u8>> for Dhcid {
+    type Error = DhcidLenError;
+
+    fn try_from(rdata: Vec<u8>) -> Result<Self, Self::Error> {
+        match rdata.get(2) {
+            Some(&DIGEST_SHA256) if rdata.len() == 3 + SHA256_LEN => Ok(Dhcid(rdata)),
+            Some(&DIGEST_SHA256) => Err(DhcidLenError(rdata.len())),
+            Some(_) if rdata.len() > 3 => Ok(Dhcid(rdata)),
+            _ => Err(DhcidLenError(rdata.len())),
+        }
+    }
+}
+
+impl Dhcid {
+    /// compute the DHCID of a client using `fqdn`. The digest is taken over
+    /// the identifier and the name in canonical (lowercase, uncompressed) wire format
+    pub fn new(source: &DhcidSource, fqdn: &Name) -> Self {
+        let mut input = source.identifier();
+        for label in fqdn.to_lowercase().iter() {
+            input.push(label.len() as u8);
+            input.extend_from_slice(label);
+        }
+        input.push(0);
+
+        let mut rdata = source.identifier_type().to_be_bytes().to_vec();
+        rdata.push(DIGEST_SHA256);
+        rdata.extend_from_slice(&Sha256::digest(&input));
+        Dhcid(rdata)
+    }
+    /// parse DHCID record data. Returns `None` if `rdata` isn't a DHCID
+    pub fn from_rdata(rdata: &RData) -> Option<Self> {
+        match rdata {
+            RData::Unknown { code, rdata } if *code == DHCID_TYPE => {
+                Dhcid::try_from(rdata.anything().to_vec()).ok()
+            }
+            _ => None,
+        }
+    }
+    /// as DHCID record data
+    pub fn to_rdata(&self) -> RData {
+        RData::Unknown {
+            code: DHCID_TYPE,
+            rdata: NULL::with(self.0.clone()),
+        }
+    }
+    /// the identifier type code
+    pub fn identifier_type(&self) -> u16 {
+        u16::from_be_bytes([self.0[0], self.0[1]])
+    }
+    /// the digest type code
+    pub fn digest_type(&self) -> u8 {
+        self.0[2]
+    }
+    /// the digest
+    pub fn digest(&self) -> &[u8] {
+        &self.0[3..]
+    }
+    /// the full RDATA
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// base64, the presentation format of DHCID RDATA
+impl fmt::Display for Dhcid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&STANDARD.encode(&self.0))
+    }
+}
+
+/// The DNS data for a lease, builds the updates to add or remove it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DdnsLease {
+    fqdn: Name,
+    addr: IpAddr,
+    dhcid: Dhcid,
+    ttl: u32,
+}
+
+impl DdnsLease {
+    /// create a lease for `addr` named `fqdn`. The TTL of the records is a
+    /// third of `lease_time`, as recommended by
+    /// [RFC 4702](https://datatracker.ietf.org/doc/html/rfc4702#section-5)
+    pub fn new(source: &DhcidSource, fqdn: Name, addr: IpAddr, lease_time: u32) -> Self {
+        Self {
+            dhcid: Dhcid::new(source, &fqdn),
+            fqdn,
+            addr,
+            ttl: lease_time / 3,
+        }
+    }
+    /// create a lease from a DHCPv4 ACK. Returns `None` if the message does
+    /// not have a fully qualified Client FQDN option, lease time or yiaddr
+    pub fn from_v4(msg: &v4::Message) -> Option<Self> {
+        let fqdn = match msg.opts().get(v4::OptionCode::ClientFQDN)? {
+            v4::DhcpOption::ClientFQDN(fqdn) => Name::from(fqdn.domain().clone()),
+            _ => return None,
+        };
+        let lease_time = match msg.opts().get(v4::OptionCode::AddressLeaseTime)? {
+            v4::DhcpOption::AddressLeaseTime(secs) => *secs,
+            _ => return None,
+        };
+        if !fqdn.is_fqdn() || msg.yiaddr().is_unspecified() {
+            return None;
+        }
+        Some(Self::new(
+            &DhcidSource::from_v4(msg),
+            fqdn,
+            msg.yiaddr().into(),
+            lease_time,
+        ))
+    }
+    /// the lease's name
+    pub fn fqdn(&self) -> &Name {
+        &self.fqdn
+    }
+    /// the leased address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+    /// the client's DHCID
+    pub fn dhcid(&self) -> &Dhcid {
+        &self.dhcid
+    }
+    /// TTL used for added records
+    pub fn ttl(&self) -> u32 {
+        self.ttl
+    }
+    /// set the TTL used for added records
+    pub fn set_ttl(&mut self, ttl: u32) -> &mut Self {
+        self.ttl = ttl;
+        self
+    }
+    /// the reverse (in-addr.arpa/ip6.arpa) name of the address
+    pub fn reverse_name(&self) -> Name {
+        Name::from(self.addr)
+    }
+
+    /// add the address & DHCID records, requires the name not be in use
+    pub fn add_forward(&self, zone: &Name) -> Message {
+        let mut msg = update(zone);
+        msg.add_answer(prereq(&self.fqdn, DNSClass::NONE, RecordType::ANY))
+            .add_name_server(self.addr_record())
+            .add_name_server(self.dhcid_record(self.ttl));
+        msg
+    }
+    /// replace the address records, requires the name's DHCID to match.
+    /// Send after [`DdnsLease::add_forward`] fails with `YXDOMAIN`
+    pub fn replace_forward(&self, zone: &Name) -> Message {
+        let mut msg = update(zone);
+        msg.add_answer(self.dhcid_record(0))
+            .add_name_server(prereq(&self.fqdn, DNSClass::ANY, self.addr_type()))
+            .add_name_server(self.addr_record());
+        msg
+    }
+    /// remove the lease's address, requires the name's DHCID to match
+    pub fn remove_forward(&self, zone: &Name) -> Message {
+        let mut addr = self.addr_record();
+        addr.set_dns_class(DNSClass::NONE).set_ttl(0);
+
+        let mut msg = update(zone);
+        msg.add_answer(self.dhcid_record(0)).add_name_server(addr);
+        msg
+    }
+    /// remove the DHCID, requires it to match & no A/AAAA records be left.
+    /// Send after [`DdnsLease::remove_forward`]
+    pub fn remove_dhcid(&self, zone: &Name) -> Message {
+        let mut msg = update(zone);
+        msg.add_answer(self.dhcid_record(0))
+            .add_answer(prereq(&self.fqdn, DNSClass::NONE, RecordType::A))
+            .add_answer(prereq(&self.fqdn, DNSClass::NONE, RecordType::AAAA))
+            .add_name_server(prereq(&self.fqdn, DNSClass::ANY, DHCID));
+        msg
+    }
+    /// replace the PTR record for the address
+    pub fn add_reverse(&self, zone: &Name) -> Message {
+        let name = self.reverse_name();
+        let mut msg = update(zone);
+        msg.add_name_server(prereq(&name, DNSClass::ANY, RecordType::PTR))
+            .add_name_server(Record::from_rdata(
+                name,
+                self.ttl,
+                RData::PTR(self.fqdn.clone()),
+            ));
+        msg
+    }
+    /// remove the PTR record for the address
+    pub fn remove_reverse(&self, zone: &Name) -> Message {
+        let mut msg = update(zone);
+        msg.add_name_server(prereq(&self.reverse_name(), DNSClass::ANY, RecordType::PTR));
+        msg
+    }
+
+    fn addr_type(&self) -> RecordType {
+        match self.addr {
+            IpAddr::V4(_) => RecordType::A,
+            IpAddr::V6(_) => RecordType::AAAA,
+        }
+    }
+    fn addr_record(&self) -> Record {
+        let rdata = match self.addr {
+            IpAddr::V4(ip) => RData::A(ip),
+            IpAddr::V6(ip) => RData::AAAA(ip),
+        };
+        Record::from_rdata(self.fqdn.clone(), self.ttl, rdata)
+    }
+    fn dhcid_record(&self, ttl: u32) -> Record {
+        Record::from_rdata(self.fqdn.clone(), ttl, self.dhcid.to_rdata())
+    }
+}
+
+/// an empty update for `zone`
+fn update(zone: &Name) -> Message {
+    let mut msg = Message::new();
+    msg.set_id(rand::random())
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Update)
+        .add_query(Query::query(zone.clone(), RecordType::SOA));
+    msg
+}
+
+/// a record with no rdata, used for RRset prerequisites & deletes
+fn prereq(name: &Name, class: DNSClass, rr_type: RecordType) -> Record {
+    let mut record = Record::with(name.clone(), rr_type, 0);
+    record.set_dns_class(class);
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trust_dns_proto::op::ResponseCode;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// stand-in for an authoritative server, applies updates to an
+    /// in-memory zone following RFC 2136 section 3
+    struct StandIn {
+        zone: Name,
+        records: Vec<Record>,
+    }
+
+    impl StandIn {
+        fn new(zone: &str) -> Self {
+            Self {
+                zone: Name::from_ascii(zone).unwrap(),
+                records: Vec::new(),
+            }
+        }
+        /// handle an update on the wire, returning the response
+        fn handle(&mut self, req: &Message) -> Result<ResponseCode> {
+            let req = Message::from_vec(&req.to_vec()?)?;
+            let rcode = self.update(&req);
+            let resp = Message::error_msg(req.id(), OpCode::Update, rcode);
+            Ok(Message::from_vec(&resp.to_vec()?)?.response_code())
+        }
+        fn update(&mut self, req: &Message) -> ResponseCode {
+            match req.queries() {
+                [zone] if zone.query_type() == RecordType::SOA && *zone.name() == self.zone => {}
+                _ => return ResponseCode::NotAuth,
+            }
+            for pr in req.answers() {
+                if !self.zone.zone_of(pr.name()) {
+                    return ResponseCode::NotZone;
+                }
+                let (name, rr_type) = (pr.name(), pr.rr_type());
+                match (pr.dns_class(), pr.data()) {
+                    (DNSClass::ANY, None) if !self.exists(name, rr_type, None) => {
+                        return match rr_type {
+                            RecordType::ANY => ResponseCode::NXDomain,
+                            _ => ResponseCode::NXRRSet,
+                        }
+                    }
+                    (DNSClass::NONE, None) if self.exists(name, rr_type, None) => {
+                        return match rr_type {
+                            RecordType::ANY => ResponseCode::YXDomain,
+                            _ => ResponseCode::YXRRSet,
+                        }
+                    }
+                    (DNSClass::IN, Some(data)) if !self.exists(name, rr_type, Some(data)) => {
+                        return ResponseCode::NXRRSet
+                    }
+                    (DNSClass::ANY | DNSClass::NONE, None) | (DNSClass::IN, Some(_)) => {}
+                    _ => return ResponseCode::FormErr,
+                }
+            }
+            for up in req.name_servers() {
+                let (name, rr_type) = (up.name(), up.rr_type());
+                match up.dns_class() {
+                    DNSClass::IN => {
+                        if !self.exists(name, rr_type, up.data()) {
+                            self.records.push(up.clone());
+                        }
+                    }
+                    DNSClass::ANY => self.records.retain(|r| {
+                        r.name() != name || (rr_type != RecordType::ANY && r.rr_type() != rr_type)
+                    }),
+                    DNSClass::NONE => self.records.retain(|r| {
+                        r.name() != name || r.rr_type() != rr_type || r.data() != up.data()
+                    }),
+                    _ => return ResponseCode::FormErr,
+                }
+            }
+            ResponseCode::NoError
+        }
+        fn exists(&self, name: &Name, rr_type: RecordType, data: Option<&RData>) -> bool {
+            self.records.iter().any(|r| {
+                r.name() == name
+                    && (rr_type == RecordType::ANY || r.rr_type() == rr_type)
+                    && (data.is_none() || r.data() == data)
+            })
+        }
+        fn get(&self, name: &Name, rr_type: RecordType) -> Vec<&RData> {
+            self.records
+                .iter()
+                .filter(|r| r.name() == name && r.rr_type() == rr_type)
+                .filter_map(|r| r.data())
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_dhcid_rfc4701() -> Result<()> {
+        // examples from RFC 4701 section 3.6
+        let duid = DhcidSource::Duid(Duid::from(hex::decode("00010006412df166010203040506")?));
+        let dhcid = Dhcid::new(&duid, &Name::from_ascii("chi6.example.com.")?);
+        assert_eq!(
+            dhcid.to_string(),
+            "AAIBY2/AuCccgoJbsaxcQc9TUapptP69lOjxfNuVAA2kjEA="
+        );
+        assert_eq!(dhcid.identifier_type(), 2);
+        assert_eq!(dhcid.digest_type(), DIGEST_SHA256);
+        assert_eq!(dhcid.digest().len(), 32);
+
+        let chaddr = DhcidSource::Chaddr {
+            htype: HType::Eth,
+            chaddr: vec![1, 2, 3, 4, 5, 6],
+        };
+        // names are compared case-insensitively
+        assert_eq!(
+            Dhcid::new(&chaddr, &Name::from_ascii("Client.Example.com.")?).to_string(),
+            "AAABxLmlskllE0MVjd57zHcWmEH3pCQ6VytcKD//7es/deY="
+        );
+
+        let id = DhcidSource::ClientId(vec![1, 7, 8, 9, 10, 11, 12]);
+        let dhcid = Dhcid::new(&id, &Name::from_ascii("chi.example.com.")?);
+        assert_eq!(
+            dhcid.to_string(),
+            "AAEBOSD+XR3Os/0LozeXVqcNc7FwCfQdWL3b/NaiUDlW2No="
+        );
+        assert_eq!(Dhcid::from_rdata(&dhcid.to_rdata()), Some(dhcid.clone()));
+
+        assert_eq!(Dhcid::try_from(vec![0, 2, 1]), Err(DhcidLenError(3)));
+        // SHA-256 digests are 32 bytes, other digest types aren't checked
+        assert_eq!(Dhcid::try_from(vec![0, 2, 1, 0]), Err(DhcidLenError(4)));
+        assert_eq!(Dhcid::try_from(vec![0; 36]), Ok(Dhcid(vec![0; 36])));
+        let mut long = dhcid.as_bytes().to_vec();
+        long.push(0);
+        assert_eq!(Dhcid::try_from(long), Err(DhcidLenError(36)));
+        let short = RData::Unknown {
+            code: DHCID_TYPE,
+            rdata: NULL::with(vec![0, 2]),
+        };
+        assert_eq!(Dhcid::from_rdata(&short), None);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dhcid_json() -> Result<()> {
+        let dhcid = Dhcid::new(
+            &DhcidSource::ClientId(vec![1, 2, 3]),
+            &Name::from_ascii("a.example.com.")?,
+        );
+        let s = serde_json::to_string(&dhcid)?;
+        assert_eq!(serde_json::from_str::<Dhcid>(&s)?, dhcid);
+        // too short to hold the type codes & a digest
+        assert!(serde_json::from_str::<Dhcid>("[0, 2]").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dhcid_source_v4() -> Result<()> {
+        let mut msg = v4::Message::default();
+        msg.set_chaddr(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            DhcidSource::from_v4(&msg),
+            DhcidSource::Chaddr {
+                htype: HType::Eth,
+                chaddr: vec![1, 2, 3, 4, 5, 6]
+            }
+        );
+        msg.opts_mut()
+            .insert(v4::DhcpOption::ClientIdentifier(vec![0, b'a']));
+        assert_eq!(
+            DhcidSource::from_v4(&msg),
+            DhcidSource::ClientId(vec![0, b'a'])
+        );
+        let duid = Duid::enterprise(1, &[1, 2, 3]);
+        msg.opts_mut()
+            .insert(ClientId::node(1, duid.clone()).into());
+        assert_eq!(DhcidSource::from_v4(&msg), DhcidSource::Duid(duid));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ddns_conflict_resolution() -> Result<()> {
+        let zone = Name::from_ascii("example.com.")?;
+        let rev_zone = Name::from_ascii("2.0.192.in-addr.arpa.")?;
+        let mut fwd = StandIn::new("example.com.");
+        let mut rev = StandIn::new("2.0.192.in-addr.arpa.");
+        let fqdn = Name::from_ascii("host.example.com.")?;
+
+        let a = DhcidSource::ClientId(vec![1, 1, 1, 1, 1, 1, 1]);
+        let b = DhcidSource::ClientId(vec![1, 2, 2, 2, 2, 2, 2]);
+        let lease = DdnsLease::new(&a, fqdn.clone(), "192.0.2.10".parse()?, 3600);
+        assert_eq!(lease.ttl(), 1200);
+
+        // name not in use, added
+        assert_eq!(
+            fwd.handle(&lease.add_forward(&zone))?,
+            ResponseCode::NoError
+        );
+        assert_eq!(
+            rev.handle(&lease.add_reverse(&rev_zone))?,
+            ResponseCode::NoError
+        );
+        assert_eq!(
+            fwd.get(&fqdn, RecordType::A),
+            [&RData::A("192.0.2.10".parse()?)]
+        );
+        assert_eq!(fwd.get(&fqdn, DHCID), [&lease.dhcid().to_rdata()]);
+        assert_eq!(
+            rev.get(&lease.reverse_name(), RecordType::PTR),
+            [&RData::PTR(fqdn.clone())]
+        );
+
+        // another client wants the same name, the DHCID doesn't match
+        let other = DdnsLease::new(&b, fqdn.clone(), "192.0.2.11".parse()?, 3600);
+        assert_eq!(
+            fwd.handle(&other.add_forward(&zone))?,
+            ResponseCode::YXDomain
+        );
+        assert_eq!(
+            fwd.handle(&other.replace_forward(&zone))?,
+            ResponseCode::NXRRSet
+        );
+        assert_eq!(
+            fwd.handle(&other.remove_forward(&zone))?,
+            ResponseCode::NXRRSet
+        );
+
+        // same client with a new address, replaced
+        let moved = DdnsLease::new(&a, fqdn.clone(), "192.0.2.12".parse()?, 3600);
+        assert_eq!(
+            fwd.handle(&moved.add_forward(&zone))?,
+            ResponseCode::YXDomain
+        );
+        assert_eq!(
+            fwd.handle(&moved.replace_forward(&zone))?,
+            ResponseCode::NoError
+        );
+        assert_eq!(
+            fwd.get(&fqdn, RecordType::A),
+            [&RData::A("192.0.2.12".parse()?)]
+        );
+        assert_eq!(
+            rev.handle(&lease.remove_reverse(&rev_zone))?,
+            ResponseCode::NoError
+        );
+        assert!(rev.records.is_empty());
+
+        // a v6 lease for the same node can share the name
+        let v6 = DdnsLease::new(&a, fqdn.clone(), "2001:db8::1".parse()?, 3600);
+        assert_eq!(
+            fwd.handle(&v6.replace_forward(&zone))?,
+            ResponseCode::NoError
+        );
+
+        // DHCID stays until all addresses are gone
+        assert_eq!(
+            fwd.handle(&moved.remove_forward(&zone))?,
+            ResponseCode::NoError
+        );
+        assert_eq!(
+            fwd.handle(&moved.remove_dhcid(&zone))?,
+            ResponseCode::YXRRSet
+        );
+        assert_eq!(
+            fwd.handle(&v6.remove_forward(&zone))?,
+            ResponseCode::NoError
+        );
+        assert_eq!(fwd.handle(&v6.remove_dhcid(&zone))?, ResponseCode::NoError);
+        assert!(fwd.records.is_empty());
+
+        // name is free again
+        assert_eq!(
+            fwd.handle(&other.add_forward(&zone))?,
+            ResponseCode::NoError
+        );
+        // outside the zone
+        assert_eq!(
+            fwd.handle(&other.add_forward(&Name::from_ascii("example.net.")?))?,
+            ResponseCode::NotAuth
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ddns_lease_from_v4() -> Result<()> {
+        use crate::v4::fqdn::{ClientFQDN, FqdnFlags};
+
+        let mut msg = v4::Message::default();
+        msg.set_chaddr(&[1, 2, 3, 4, 5, 6])
+            .set_yiaddr([192, 0, 2, 10]);
+        msg.opts_mut().insert(v4::DhcpOption::AddressLeaseTime(300));
+        assert_eq!(DdnsLease::from_v4(&msg), None);
+
+        let fqdn = Name::from_ascii("client.example.com.")?;
+        msg.opts_mut()
+            .insert(v4::DhcpOption::ClientFQDN(ClientFQDN::new(
+                FqdnFlags::default().set_s(true),
+                fqdn.clone().into(),
+            )));
+        let lease = DdnsLease::from_v4(&msg).unwrap();
+        assert_eq!(lease.fqdn(), &fqdn);
+        assert_eq!(lease.addr(), "192.0.2.10".parse::<IpAddr>()?);
+        assert_eq!(lease.ttl(), 100);
+        assert_eq!(
+            lease.dhcid().to_string(),
+            "AAABxLmlskllE0MVjd57zHcWmEH3pCQ6VytcKD//7es/deY="
+        );
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 //! Decodable trait & Decoder
-use crate::error::{DecodeError, DecodeResult};
+use crate::error::{DecodeError, DecodeResult, PathSegment};
 
 use std::{
     array::TryFromSliceError,
@@ -26,12 +26,52 @@ pub trait Decodable: Sized {
 #[derive(Debug)]
 pub struct Decoder<'a> {
     buffer: &'a [u8],
+    /// position of `buffer` relative to the start of the original input
+    offset: usize,
 }
 
 impl<'a> Decoder<'a> {
     /// Create a new Decoder
     pub fn new(buffer: &'a [u8]) -> Self {
-        Decoder { buffer }
+        Decoder { buffer, offset: 0 }
+    }
+
+    /// Create a new Decoder for `buffer`, where `buffer` starts at `offset`
+    /// bytes into some larger input. Errors will report offsets relative
+    /// to that input.
+    pub fn new_at(buffer: &'a [u8], offset: usize) -> Self {
+        Decoder { buffer, offset }
+    }
+
+    /// number of bytes read so far, relative to the start of the original input
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// read `len` bytes and return a new `Decoder` over them. The returned
+    /// decoder keeps track of its position in the original input, so errors
+    /// from nested types report the correct byte offset.
+    pub fn sub_decoder(&mut self, len: usize) -> DecodeResult<Decoder<'a>> {
+        let offset = self.offset;
+        Ok(Decoder::new_at(self.read_slice(len)?, offset))
+    }
+
+    /// decode a named field, any error returned by `f` will have the field
+    /// name and the offset it occurred at attached.
+    ///
+    /// ```
+    /// # use dhcproto::{Decoder, error::DecodeError};
+    /// let mut d = Decoder::new(&[0, 1, 2]);
+    /// let err = d.field("t1", |d| d.read_u32()).unwrap_err();
+    /// assert_eq!(err.offset(), Some(0));
+    /// assert_eq!(err.path().unwrap().to_string(), "t1");
+    /// assert!(matches!(err.kind(), DecodeError::NotEnoughBytes));
+    /// ```
+    pub fn field<T, F>(&mut self, name: &'static str, f: F) -> DecodeResult<T>
+    where
+        F: FnOnce(&mut Self) -> DecodeResult<T>,
+    {
+        f(self).map_err(|err| err.with_context(self.offset, PathSegment::Field(name)))
     }
 
     /// peek at the next byte without advancing the internal pointer
@@ -79,6 +119,7 @@ impl<'a> Decoder<'a> {
         }
         let (slice, remaining) = self.buffer.split_at(N);
         self.buffer = remaining;
+        self.offset += N;
         // can't panic-- condition checked above
         Ok(slice.try_into().unwrap())
     }
@@ -130,6 +171,7 @@ impl<'a> Decoder<'a> {
         }
         let (slice, remaining) = self.buffer.split_at(len);
         self.buffer = remaining;
+        self.offset += len;
         Ok(slice)
     }
 
@@ -203,7 +245,7 @@ impl<'a> Decoder<'a> {
     }
 
     /// return slice of buffer start at index of unread data
-    pub fn buffer(&self) -> &[u8] {
+    pub fn buffer(&self) -> &'a [u8] {
         self.buffer
     }
 }
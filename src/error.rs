@@ -1,5 +1,5 @@
 //! Error types for Encoding/Decoding
-use std::io;
+use std::{fmt, io};
 
 use thiserror::Error;
 
@@ -44,6 +44,101 @@ pub enum DecodeError {
     /// Unknown decode error
     #[error("unknown error")]
     Unknown(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    /// an error with the location it occurred at
+    #[error("{source} at byte {offset} (`{path}`)")]
+    Context {
+        /// byte offset into the original input
+        offset: usize,
+        /// path to the field that failed to decode
+        path: FieldPath,
+        /// the underlying error
+        source: Box<DecodeError>,
+    },
+}
+
+impl DecodeError {
+    /// byte offset into the original input where the error occurred, if known
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            DecodeError::Context { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// path to the field that was being decoded when the error occurred, if known
+    pub fn path(&self) -> Option<&FieldPath> {
+        match self {
+            DecodeError::Context { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// the underlying error, without any location information
+    pub fn kind(&self) -> &DecodeError {
+        match self {
+            DecodeError::Context { source, .. } => source.kind(),
+            err => err,
+        }
+    }
+
+    /// attach location info to the error. If the error already has a location,
+    /// `segment` is prepended to its path and the original offset is kept.
+    pub(crate) fn with_context(self, offset: usize, segment: PathSegment) -> Self {
+        match self {
+            DecodeError::Context {
+                offset,
+                mut path,
+                source,
+            } => {
+                path.0.insert(0, segment);
+                DecodeError::Context {
+                    offset,
+                    path,
+                    source,
+                }
+            }
+            err => DecodeError::Context {
+                offset,
+                path: FieldPath(vec![segment]),
+                source: Box::new(err),
+            },
+        }
+    }
+}
+
+/// Location of a field within a message, displayed like
+/// `opts[IANA].opts[IAAddr].valid_life`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FieldPath(Vec<PathSegment>);
+
+impl FieldPath {
+    /// iterate over the segments of this path, outermost first
+    pub fn segments(&self) -> impl Iterator<Item = &PathSegment> {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if i == 0 => write!(f, "{name}")?,
+                PathSegment::Field(name) => write!(f, ".{name}")?,
+                PathSegment::Opt(code) => write!(f, "[{code}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single segment of a [`FieldPath`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// a named field
+    Field(&'static str),
+    /// an option, identified by its option code
+    Opt(String),
 }
 
 /// Returned from types that encode
@@ -69,6 +164,20 @@ pub enum EncodeError {
     /// DNS encoding error from trust-dns
     #[error("domain encoding error {0}")]
     DomainEncodeError(#[from] trust_dns_proto::error::ProtoError),
+
+    /// option value is too long for its 1 byte length
+    #[error("option value of {len} bytes exceeds the max length of 255")]
+    OptionTooLarge {
+        /// size of the value
+        len: usize,
+    },
+
+    /// message is too large for a 2 byte TCP length prefix
+    #[error("message of {len} bytes is too large to frame")]
+    FrameTooLarge {
+        /// size of the message
+        len: usize,
+    },
 }
 
 /// Convenience type for encode errors
@@ -0,0 +1,67 @@
+//! TCP framing for leasequery & failover
+//!
+//! Bulk leasequery ([RFC 5460], [RFC 6926]), active leasequery ([RFC 7724])
+//! & DHCPv6 failover ([RFC 8156]) run over TCP, each message is preceded by
+//! its length as a 2 byte big-endian integer.
+//!
+//! [RFC 5460]: https://datatracker.ietf.org/doc/html/rfc5460#section-5.1
+//! [RFC 6926]: https://datatracker.ietf.org/doc/html/rfc6926#section-6.3
+//! [RFC 7724]: https://datatracker.ietf.org/doc/html/rfc7724
+//! [RFC 8156]: https://datatracker.ietf.org/doc/html/rfc8156
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeResult, EncodeError, EncodeResult},
+};
+
+/// encode `msg` with its 2 byte length prefix
+pub fn encode_frame<T: Encodable>(msg: &T) -> EncodeResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    msg.encode(&mut Encoder::new(&mut buf))?;
+    let len =
+        u16::try_from(buf.len()).map_err(|_| EncodeError::FrameTooLarge { len: buf.len() })?;
+    let mut frame = len.to_be_bytes().to_vec();
+    frame.extend(buf);
+    Ok(frame)
+}
+
+/// Splits a TCP byte stream into length prefixed messages. Feed it bytes as
+/// they are read, then take messages out once they are complete
+#[derive(Debug, Default, Clone)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+}
+
+impl FrameDecoder {
+    /// create an empty decoder
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// add bytes read from the stream
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+    /// number of bytes buffered that aren't part of a returned frame yet
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+    /// take the next complete frame, without its length prefix
+    pub fn next_frame(&mut self) -> Option<Vec<u8>> {
+        let len = match self.buf[..] {
+            [a, b, ..] => u16::from_be_bytes([a, b]) as usize,
+            _ => return None,
+        };
+        if self.buf.len() < len + 2 {
+            return None;
+        }
+        let frame = self.buf[2..len + 2].to_vec();
+        self.buf.drain(..len + 2);
+        Some(frame)
+    }
+    /// take & decode the next complete message
+    pub fn next_message<T: Decodable>(&mut self) -> DecodeResult<Option<T>> {
+        self.next_frame()
+            .map(|frame| T::decode(&mut Decoder::new(&frame)))
+            .transpose()
+    }
+}
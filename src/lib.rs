@@ -88,10 +88,16 @@ pub use encoder::{Encodable, Encoder};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 pub use trust_dns_proto::rr::Name;
+use trust_dns_proto::serialize::binary::{BinDecodable, BinDecoder, BinEncoder};
 
+use crate::error::{DecodeResult, EncodeResult};
+
+#[cfg(feature = "ddns")]
+pub mod ddns;
 pub mod decoder;
 pub mod encoder;
 pub mod error;
+pub mod framing;
 pub mod v4;
 pub mod v6;
 
@@ -102,6 +108,55 @@ impl Domain {
     pub fn new(name: Name) -> Self {
         Domain(name)
     }
+
+    /// decode a list of domain names, following any compression pointers.
+    /// pointers are relative to the start of `buf`, which must hold the whole
+    /// list (ex. after concatenating a split v4 option)
+    pub(crate) fn decode_list(buf: &[u8]) -> Vec<Domain> {
+        let mut name_decoder = BinDecoder::new(buf);
+        let mut names = Vec::new();
+        while let Ok(name) = Name::read(&mut name_decoder) {
+            names.push(Domain(name));
+        }
+        names
+    }
+
+    /// decode an uncompressed name that may be partial (missing the root
+    /// label), as used by the client FQDN options
+    pub(crate) fn decode_partial(buf: &[u8]) -> DecodeResult<Domain> {
+        match buf.last() {
+            None => Ok(Domain(Name::new())),
+            Some(0) => Ok(Domain(Name::read(&mut BinDecoder::new(buf))?)),
+            Some(_) => {
+                let mut full = buf.to_vec();
+                full.push(0);
+                let mut name = Name::read(&mut BinDecoder::new(&full))?;
+                name.set_fqdn(false);
+                Ok(Domain(name))
+            }
+        }
+    }
+
+    /// encode an uncompressed name, partial names are written without the root label
+    pub(crate) fn encode_partial(&self) -> EncodeResult<Vec<u8>> {
+        let mut buf = Domain::encode_list(std::slice::from_ref(self), false)?;
+        if !self.0.is_fqdn() {
+            buf.pop();
+        }
+        Ok(buf)
+    }
+
+    /// encode a list of domain names. With `compress`, names use RFC 1035
+    /// compression with pointers relative to the start of the list, so the
+    /// result must be written (or split into long options) as a single unit
+    pub(crate) fn encode_list(names: &[Domain], compress: bool) -> EncodeResult<Vec<u8>> {
+        let mut buf = Vec::new();
+        let mut name_encoder = BinEncoder::new(&mut buf);
+        for name in names {
+            name.0.emit_as_canonical(&mut name_encoder, !compress)?;
+        }
+        Ok(buf)
+    }
 }
 
 impl AsRef<Name> for Domain {
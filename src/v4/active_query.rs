@@ -0,0 +1,681 @@
+//! Active leasequery ([RFC 7724])
+//!
+//! A requestor opens a TCP connection, optionally negotiates TLS with a
+//! `DHCPTLS` exchange, then sends a `DHCPACTIVELEASEQUERY`. If the query
+//! holds a Query Start Time (154) the server first replays the lease changes
+//! since then ("catch-up"), ending with a `DHCPLEASEQUERYSTATUS` of
+//! [`Code::CatchUpComplete`]. After that every lease change is sent as it
+//! happens, until the server ends the stream with a `DHCPLEASEQUERYDONE`.
+//!
+//! [`Requestor`] & [`Responder`] don't do any IO, they frame messages &
+//! track where the connection is. Keep [`Requestor::base_time`] around to
+//! resume from where a dropped connection left off.
+//!
+//! ```rust
+//! use dhcproto::v4::{
+//!     active_query::{query, status, ActiveEvent, LeaseChange, Requestor, Responder},
+//!     bulk_query::{Code, QueryState},
+//!     leasequery::{LeaseQueryResult, LeaseRecord},
+//! };
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut requestor = Requestor::new();
+//! let mut responder = Responder::new();
+//!
+//! // catch up from where the last connection stopped
+//! responder.receive(&requestor.send(&query(Some(1_000)))?);
+//! let query = responder.poll()?.unwrap();
+//!
+//! let mut change = LeaseChange::new(LeaseQueryResult::Active(LeaseRecord::new("10.0.0.10".parse()?)));
+//! change.state = Some(QueryState::Active);
+//! change.base_time = Some(1_060);
+//! let mut bytes = responder.send(&change.reply(&query)?)?;
+//! bytes.extend(responder.send(&status(&query, Code::CatchUpComplete, ""))?);
+//! bytes.extend(responder.send(&change.reply(&query)?)?);
+//!
+//! requestor.receive(&bytes);
+//! let events = requestor.events().collect::<Result<Vec<_>, _>>()?;
+//! assert!(matches!(events[0], ActiveEvent::CatchUp(_)));
+//! assert_eq!(events[1], ActiveEvent::CatchUpComplete);
+//! assert!(matches!(events[2], ActiveEvent::Update(_)));
+//! assert_eq!(requestor.base_time(), Some(1_060));
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 7724]: https://datatracker.ietf.org/doc/html/rfc7724
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::v4::{
+    bulk_query::{
+        encode_frame, status as status_code, BulkQueryError, Code, DataSourceFlags, FrameDecoder,
+        QueryState,
+    },
+    leasequery::LeaseQueryResult,
+    DhcpOption, HType, HwAddrError, Message, MessageType, Opcode, OptionCode,
+};
+
+/// build a `DHCPACTIVELEASEQUERY` with a random xid. With a
+/// `query_start_time` (seconds since the epoch, usually the last
+/// [`Requestor::base_time`]) the server sends the changes since then before
+/// any updates
+pub fn query(query_start_time: Option<u32>) -> Message {
+    let mut msg = Message::default();
+    msg.set_htype(HType::from(0));
+    msg.opts_mut()
+        .insert(DhcpOption::MessageType(MessageType::ActiveLeaseQuery));
+    if let Some(time) = query_start_time {
+        msg.opts_mut()
+            .insert(DhcpOption::BulkLeaseQueryQueryStartTime(time));
+    }
+    msg.opts_mut().insert(DhcpOption::ParameterRequestList(vec![
+        OptionCode::AddressLeaseTime,
+        OptionCode::ClientIdentifier,
+        OptionCode::RelayAgentInformation,
+        OptionCode::ClientLastTransactionTime,
+        OptionCode::AssociatedIp,
+        OptionCode::BaseTime,
+        OptionCode::StartTimeOfState,
+        OptionCode::DhcpState,
+        OptionCode::DataSource,
+    ]));
+    msg
+}
+
+/// build a `DHCPLEASEQUERYSTATUS` for `query`. A status code option is
+/// included unless `status` is [`Code::Success`] with no message
+pub fn status(query: &Message, status: Code, message: &str) -> Message {
+    let mut msg = reply_to(query, MessageType::LeaseQueryStatus);
+    if status != Code::Success || !message.is_empty() {
+        msg.opts_mut().insert(DhcpOption::BulkLeaseQueryStatusCode(
+            status,
+            message.to_owned(),
+        ));
+    }
+    msg
+}
+
+/// build a `DHCPTLS` with a random xid, asking the server to start TLS
+pub fn tls() -> Message {
+    let mut msg = Message::default();
+    msg.set_htype(HType::from(0));
+    msg.opts_mut()
+        .insert(DhcpOption::MessageType(MessageType::Tls));
+    msg
+}
+
+/// build the server's `DHCPTLS` accepting `request`. The TLS handshake
+/// starts once it is sent. To refuse, send a [`status`] of
+/// [`Code::TlsConnectionRefused`] instead
+pub fn tls_reply(request: &Message) -> Message {
+    reply_to(request, MessageType::Tls)
+}
+
+fn reply_to(request: &Message, msg_type: MessageType) -> Message {
+    let mut msg = Message::default();
+    msg.set_opcode(Opcode::BootReply)
+        .set_xid(request.xid())
+        .set_htype(HType::from(0));
+    msg.opts_mut().insert(DhcpOption::MessageType(msg_type));
+    msg
+}
+
+/// true for status codes that end an active leasequery
+fn terminates(code: Code) -> bool {
+    !matches!(
+        code,
+        Code::Success | Code::DataMissing | Code::CatchUpComplete
+    )
+}
+
+/// A lease change sent on an active leasequery connection
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaseChange {
+    /// the lease, or the address it no longer leases
+    pub result: LeaseQueryResult,
+    /// state of the address (156)
+    pub state: Option<QueryState>,
+    /// server time the change was sent, seconds since the epoch (152)
+    pub base_time: Option<u32>,
+    /// seconds before `base_time` the address entered `state` (153)
+    pub start_time_of_state: Option<u32>,
+    /// whether the change came from the server's failover partner (157)
+    pub data_source: Option<DataSourceFlags>,
+}
+
+impl LeaseChange {
+    /// a change with no state or times
+    pub fn new(result: LeaseQueryResult) -> Self {
+        Self {
+            result,
+            state: None,
+            base_time: None,
+            start_time_of_state: None,
+            data_source: None,
+        }
+    }
+    /// read a change from a `DHCPLEASEACTIVE`/`DHCPLEASEUNASSIGNED`.
+    /// Returns `None` if `msg` isn't a leasequery reply
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        let opts = msg.opts();
+        Some(Self {
+            result: LeaseQueryResult::from_message(msg)?,
+            state: match opts.get(OptionCode::DhcpState) {
+                Some(DhcpOption::BulkLeaseQueryDhcpState(state)) => Some(*state),
+                _ => None,
+            },
+            base_time: match opts.get(OptionCode::BaseTime) {
+                Some(DhcpOption::BulkLeaseQueryBaseTime(time)) => Some(*time),
+                _ => None,
+            },
+            start_time_of_state: match opts.get(OptionCode::StartTimeOfState) {
+                Some(DhcpOption::BulkLeasQueryStartTimeOfState(secs)) => Some(*secs),
+                _ => None,
+            },
+            data_source: match opts.get(OptionCode::DataSource) {
+                Some(DhcpOption::BulkLeaseQueryDataSource(src)) => Some(*src),
+                _ => None,
+            },
+        })
+    }
+    /// when the address entered its current state, seconds since the epoch
+    pub fn state_since(&self) -> Option<u32> {
+        self.base_time?.checked_sub(self.start_time_of_state?)
+    }
+    /// build the server's message sending this change on `query`'s
+    /// connection. Returns an error if the lease's hardware address doesn't
+    /// fit in chaddr
+    pub fn reply(&self, query: &Message) -> Result<Message, HwAddrError> {
+        let mut msg = self.result.reply(query)?;
+        // unlike a reply to a single query, a freed address is always
+        // sent as `DHCPLEASEUNASSIGNED`
+        if let LeaseQueryResult::Unassigned(ip) = self.result {
+            msg.set_ciaddr(ip);
+            msg.opts_mut()
+                .insert(DhcpOption::MessageType(MessageType::LeaseUnassigned));
+        }
+        let opts = msg.opts_mut();
+        if let Some(state) = self.state {
+            opts.insert(DhcpOption::BulkLeaseQueryDhcpState(state));
+        }
+        if let Some(time) = self.base_time {
+            opts.insert(DhcpOption::BulkLeaseQueryBaseTime(time));
+        }
+        if let Some(secs) = self.start_time_of_state {
+            opts.insert(DhcpOption::BulkLeasQueryStartTimeOfState(secs));
+        }
+        if let Some(src) = self.data_source {
+            opts.insert(DhcpOption::BulkLeaseQueryDataSource(src));
+        }
+        Ok(msg)
+    }
+}
+
+/// Where an active leasequery connection is
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Phase {
+    /// no query sent yet
+    #[default]
+    Idle,
+    /// receiving the changes since the query start time
+    CatchUp,
+    /// receiving changes as they happen
+    Updates,
+    /// the server ended the stream
+    Done,
+}
+
+/// Something received by an active leasequery [`Requestor`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActiveEvent {
+    /// the server accepted TLS, start the handshake before sending anything
+    /// else
+    TlsAccepted,
+    /// the server refused TLS. It may close the connection
+    TlsRefused {
+        /// status message
+        message: String,
+    },
+    /// a change that happened before the query was sent
+    CatchUp(LeaseChange),
+    /// the server can't send every change since the query start time, the
+    /// catch-up data is incomplete
+    DataMissing {
+        /// status message
+        message: String,
+    },
+    /// all catch-up data was sent, only updates follow
+    CatchUpComplete,
+    /// a change as it happened
+    Update(LeaseChange),
+    /// the server ended the stream, either by a `DHCPLEASEQUERYDONE` or an
+    /// unsuccessful status code
+    Done {
+        /// status code, `Success` if the server didn't send one
+        status: Code,
+        /// status message
+        message: String,
+    },
+}
+
+/// The requestor end of an active leasequery connection
+#[derive(Debug, Default, Clone)]
+pub struct Requestor {
+    frames: FrameDecoder,
+    xid: Option<u32>,
+    tls_xid: Option<u32>,
+    phase: Phase,
+    base_time: Option<u32>,
+}
+
+impl Requestor {
+    /// create a requestor that hasn't sent a query
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// send a `DHCPACTIVELEASEQUERY` or `DHCPTLS`, returns the bytes to
+    /// write to the stream. Only one query can be sent per connection
+    pub fn send(&mut self, msg: &Message) -> Result<Vec<u8>, BulkQueryError> {
+        let frame = encode_frame(msg)?;
+        match msg.opts().msg_type() {
+            Some(MessageType::Tls) => {
+                self.tls_xid = Some(msg.xid());
+            }
+            _ => {
+                if let Some(xid) = self.xid {
+                    return Err(BulkQueryError::DuplicateXid(xid));
+                }
+                self.xid = Some(msg.xid());
+                self.phase = match msg.opts().get(OptionCode::QueryStartTime) {
+                    Some(_) => Phase::CatchUp,
+                    None => Phase::Updates,
+                };
+            }
+        }
+        Ok(frame)
+    }
+    /// add bytes read from the stream
+    pub fn receive(&mut self, bytes: &[u8]) {
+        self.frames.extend(bytes);
+    }
+    /// the next event from the received bytes, `None` if more are needed
+    pub fn poll(&mut self) -> Result<Option<ActiveEvent>, BulkQueryError> {
+        let msg = match self.frames.next_message::<Message>()? {
+            Some(msg) => msg,
+            None => return Ok(None),
+        };
+        let xid = msg.xid();
+        let status = status_code(&msg).map(|(code, message)| (code, message.to_owned()));
+        let msg_type = msg.opts().msg_type();
+
+        if self.tls_xid == Some(xid) {
+            self.tls_xid = None;
+            return match (msg_type, status) {
+                (Some(MessageType::Tls), _) => Ok(Some(ActiveEvent::TlsAccepted)),
+                (_, Some((Code::TlsConnectionRefused, message))) => {
+                    Ok(Some(ActiveEvent::TlsRefused { message }))
+                }
+                _ => Err(BulkQueryError::UnexpectedMessage(msg_type)),
+            };
+        }
+        if self.xid != Some(xid) || self.phase == Phase::Done {
+            return Err(BulkQueryError::UnknownXid(xid));
+        }
+        if let Some(DhcpOption::BulkLeaseQueryBaseTime(time)) = msg.opts().get(OptionCode::BaseTime)
+        {
+            self.base_time = Some(*time);
+        }
+
+        if msg_type == Some(MessageType::LeaseQueryDone)
+            || matches!(status, Some((code, _)) if terminates(code))
+        {
+            self.phase = Phase::Done;
+            let (status, message) = status.unwrap_or((Code::Success, String::new()));
+            return Ok(Some(ActiveEvent::Done { status, message }));
+        }
+        if msg_type == Some(MessageType::LeaseQueryStatus) {
+            return match status {
+                Some((Code::CatchUpComplete, _)) => {
+                    self.phase = Phase::Updates;
+                    Ok(Some(ActiveEvent::CatchUpComplete))
+                }
+                Some((Code::DataMissing, message)) => {
+                    Ok(Some(ActiveEvent::DataMissing { message }))
+                }
+                // a success status says nothing, wait for the next message
+                _ => self.poll(),
+            };
+        }
+        match LeaseChange::from_message(&msg) {
+            Some(change) if self.phase == Phase::CatchUp => Ok(Some(ActiveEvent::CatchUp(change))),
+            Some(change) => Ok(Some(ActiveEvent::Update(change))),
+            None => Err(BulkQueryError::UnexpectedMessage(msg_type)),
+        }
+    }
+    /// iterate over the events from the received bytes, stopping when more
+    /// are needed
+    pub fn events(&mut self) -> Events<'_> {
+        Events { requestor: self }
+    }
+    /// where the connection is
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+    /// the most recent Base Time (152) received. Send it as the query start
+    /// time when reconnecting to catch up on the changes missed
+    pub fn base_time(&self) -> Option<u32> {
+        self.base_time
+    }
+}
+
+/// Iterator over the events a [`Requestor`] has received, see
+/// [`Requestor::events`]
+#[derive(Debug)]
+pub struct Events<'a> {
+    requestor: &'a mut Requestor,
+}
+
+impl Iterator for Events<'_> {
+    type Item = Result<ActiveEvent, BulkQueryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.requestor.poll().transpose()
+    }
+}
+
+/// The server end of an active leasequery connection
+#[derive(Debug, Default, Clone)]
+pub struct Responder {
+    frames: FrameDecoder,
+    xid: Option<u32>,
+    tls_xid: Option<u32>,
+}
+
+impl Responder {
+    /// create a responder that hasn't received a query
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// add bytes read from the stream
+    pub fn receive(&mut self, bytes: &[u8]) {
+        self.frames.extend(bytes);
+    }
+    /// the next `DHCPACTIVELEASEQUERY` or `DHCPTLS` from the received bytes,
+    /// `None` if more are needed
+    pub fn poll(&mut self) -> Result<Option<Message>, BulkQueryError> {
+        let msg = match self.frames.next_message::<Message>()? {
+            Some(msg) => msg,
+            None => return Ok(None),
+        };
+        match msg.opts().msg_type() {
+            Some(MessageType::Tls) => {
+                self.tls_xid = Some(msg.xid());
+            }
+            Some(MessageType::ActiveLeaseQuery) => {
+                if let Some(xid) = self.xid {
+                    return Err(BulkQueryError::DuplicateXid(xid));
+                }
+                self.xid = Some(msg.xid());
+            }
+            msg_type => return Err(BulkQueryError::UnexpectedMessage(msg_type)),
+        }
+        Ok(Some(msg))
+    }
+    /// send a message on the connection, returns the bytes to write to the
+    /// stream. Sending a `DHCPLEASEQUERYDONE` or an unsuccessful status code
+    /// ends the stream
+    pub fn send(&mut self, msg: &Message) -> Result<Vec<u8>, BulkQueryError> {
+        let xid = msg.xid();
+        if self.tls_xid == Some(xid) {
+            self.tls_xid = None;
+            return Ok(encode_frame(msg)?);
+        }
+        if self.xid != Some(xid) {
+            return Err(BulkQueryError::UnknownXid(xid));
+        }
+        let frame = encode_frame(msg)?;
+        if msg.opts().has_msg_type(MessageType::LeaseQueryDone)
+            || matches!(status_code(msg), Some((code, _)) if terminates(code))
+        {
+            self.xid = None;
+        }
+        Ok(frame)
+    }
+    /// true if the stream hasn't been ended
+    pub fn is_active(&self) -> bool {
+        self.xid.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v4::{bulk_query::done, leasequery::LeaseRecord};
+    use std::net::Ipv4Addr;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    /// an in-process server holding a log of lease changes by base time
+    struct Server {
+        responder: Responder,
+        log: Vec<LeaseChange>,
+        oldest: u32,
+    }
+
+    impl Server {
+        fn new(oldest: u32) -> Self {
+            Self {
+                responder: Responder::new(),
+                log: Vec::new(),
+                oldest,
+            }
+        }
+        fn change(ip: Ipv4Addr, state: QueryState, time: u32) -> LeaseChange {
+            let result = match state {
+                QueryState::Active => LeaseQueryResult::Active(LeaseRecord::new(ip)),
+                _ => LeaseQueryResult::Unassigned(ip),
+            };
+            let mut change = LeaseChange::new(result);
+            change.state = Some(state);
+            change.base_time = Some(time);
+            change.start_time_of_state = Some(0);
+            change
+        }
+        /// read the query, then send catch-up data. Returns the query
+        fn accept(&mut self, bytes: &[u8], out: &mut Vec<u8>) -> Result<Message> {
+            self.responder.receive(bytes);
+            let query = self.responder.poll()?.unwrap();
+            if let Some(DhcpOption::BulkLeaseQueryQueryStartTime(start)) =
+                query.opts().get(OptionCode::QueryStartTime)
+            {
+                if *start < self.oldest {
+                    out.extend(self.responder.send(&status(
+                        &query,
+                        Code::DataMissing,
+                        "log truncated",
+                    ))?);
+                }
+                for change in &self.log {
+                    if change.base_time.unwrap() >= *start {
+                        out.extend(self.responder.send(&change.reply(&query)?)?);
+                    }
+                }
+                out.extend(
+                    self.responder
+                        .send(&status(&query, Code::CatchUpComplete, ""))?,
+                );
+            }
+            Ok(query)
+        }
+    }
+
+    #[test]
+    fn test_lease_change() -> Result<()> {
+        let query = query(None);
+        let mut change = Server::change("10.0.0.5".parse()?, QueryState::Active, 500);
+        change.start_time_of_state = Some(20);
+        change.data_source = Some(DataSourceFlags::default().set_remote());
+        assert_eq!(change.state_since(), Some(480));
+        assert_eq!(
+            LeaseChange::from_message(&change.reply(&query)?),
+            Some(change)
+        );
+        assert_eq!(LeaseChange::from_message(&query), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_catch_up_then_updates() -> Result<()> {
+        let mut server = Server::new(100);
+        server
+            .log
+            .push(Server::change("10.0.0.1".parse()?, QueryState::Active, 100));
+        server
+            .log
+            .push(Server::change("10.0.0.2".parse()?, QueryState::Active, 200));
+        server.log.push(Server::change(
+            "10.0.0.1".parse()?,
+            QueryState::Expired,
+            300,
+        ));
+
+        // resume from time 200, TLS first
+        let mut requestor = Requestor::new();
+        let mut server_bytes = Vec::new();
+        server.responder.receive(&requestor.send(&tls())?);
+        let request = server.responder.poll()?.unwrap();
+        server_bytes.extend(server.responder.send(&tls_reply(&request))?);
+        let query = server.accept(&requestor.send(&query(Some(200)))?, &mut server_bytes)?;
+        assert_eq!(requestor.phase(), Phase::CatchUp);
+        assert!(matches!(
+            requestor.send(&query),
+            Err(BulkQueryError::DuplicateXid(_))
+        ));
+
+        // live updates
+        let update = Server::change("10.0.0.3".parse()?, QueryState::Active, 400);
+        server_bytes.extend(server.responder.send(&update.reply(&query)?)?);
+        server_bytes.extend(server.responder.send(&done(&query, Code::Success, ""))?);
+        assert!(!server.responder.is_active());
+
+        // deliver a byte at a time
+        let mut events = Vec::new();
+        for b in server_bytes {
+            requestor.receive(&[b]);
+            for event in requestor.events() {
+                events.push(event?);
+            }
+        }
+        assert_eq!(
+            events,
+            vec![
+                ActiveEvent::TlsAccepted,
+                ActiveEvent::CatchUp(server.log[1].clone()),
+                ActiveEvent::CatchUp(server.log[2].clone()),
+                ActiveEvent::CatchUpComplete,
+                ActiveEvent::Update(update),
+                ActiveEvent::Done {
+                    status: Code::Success,
+                    message: String::new()
+                },
+            ]
+        );
+        assert_eq!(requestor.phase(), Phase::Done);
+        assert_eq!(requestor.base_time(), Some(400));
+        Ok(())
+    }
+
+    #[test]
+    fn test_data_missing() -> Result<()> {
+        let mut server = Server::new(100);
+        let mut requestor = Requestor::new();
+        let mut bytes = Vec::new();
+        server.accept(&requestor.send(&query(Some(50)))?, &mut bytes)?;
+        requestor.receive(&bytes);
+        assert_eq!(
+            requestor
+                .events()
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+            vec![
+                ActiveEvent::DataMissing {
+                    message: "log truncated".to_owned()
+                },
+                ActiveEvent::CatchUpComplete
+            ]
+        );
+        assert_eq!(requestor.phase(), Phase::Updates);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unexpected() -> Result<()> {
+        let mut requestor = Requestor::new();
+        let mut responder = Responder::new();
+
+        let mut discover = Message::default();
+        discover
+            .opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Discover));
+        responder.receive(&encode_frame(&discover)?);
+        assert!(matches!(
+            responder.poll(),
+            Err(BulkQueryError::UnexpectedMessage(Some(
+                MessageType::Discover
+            )))
+        ));
+
+        // an ack in reply to DHCPTLS
+        let request = tls();
+        requestor.send(&request)?;
+        let mut ack = Message::default();
+        ack.set_xid(request.xid());
+        ack.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Ack));
+        requestor.receive(&encode_frame(&ack)?);
+        assert!(matches!(
+            requestor.poll(),
+            Err(BulkQueryError::UnexpectedMessage(Some(MessageType::Ack)))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_refused() -> Result<()> {
+        let mut requestor = Requestor::new();
+        let mut responder = Responder::new();
+
+        // no catch-up without a start time
+        responder.receive(&requestor.send(&tls())?);
+        let request = responder.poll()?.unwrap();
+        responder.receive(&requestor.send(&query(None))?);
+        let query = responder.poll()?.unwrap();
+        assert_eq!(requestor.phase(), Phase::Updates);
+
+        let mut bytes = responder.send(&status(&request, Code::TlsConnectionRefused, "no"))?;
+        bytes.extend(responder.send(&status(&query, Code::ConnectionActive, ""))?);
+        assert!(!responder.is_active());
+        requestor.receive(&bytes);
+        assert_eq!(
+            requestor.poll()?,
+            Some(ActiveEvent::TlsRefused {
+                message: "no".to_owned()
+            })
+        );
+        assert_eq!(
+            requestor.poll()?,
+            Some(ActiveEvent::Done {
+                status: Code::ConnectionActive,
+                message: String::new()
+            })
+        );
+
+        // nothing after the stream ends
+        requestor.receive(&encode_frame(&status(&query, Code::CatchUpComplete, ""))?);
+        assert!(matches!(
+            requestor.poll(),
+            Err(BulkQueryError::UnknownXid(_))
+        ));
+        Ok(())
+    }
+}
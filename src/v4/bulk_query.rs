@@ -1,7 +1,58 @@
-use std::fmt;
+//! Bulk leasequery ([RFC 6926])
+//!
+//! Bulk leasequery runs over TCP, each message is preceded by a 2 byte
+//! length. [`FrameDecoder`] & [`encode_frame`] (re-exported from
+//! [`crate::framing`]) handle the framing, while
+//! [`Requestor`] & [`Responder`] track the queries in progress on a
+//! connection without doing any IO themselves.
+//!
+//! ```rust
+//! use dhcproto::v4::{
+//!     bulk_query::{done, BulkQuery, Code, Requestor, RequestorEvent, Responder},
+//!     leasequery::{LeaseQueryResult, LeaseRecord},
+//! };
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut requestor = Requestor::new();
+//! let mut responder = Responder::new();
+//!
+//! let query = BulkQuery::RemoteId(b"subscriber-1".to_vec()).to_message()?;
+//! let bytes = requestor.send(&query)?;
+//! // .. write `bytes` to the TCP stream, the server reads them
+//! responder.receive(&bytes);
+//! let query = responder.poll()?.unwrap();
+//! assert!(matches!(BulkQuery::from_message(&query), Some(BulkQuery::RemoteId(_))));
+//!
+//! // send each lease, then finish the query
+//! let lease = LeaseQueryResult::Active(LeaseRecord::new("10.0.0.10".parse()?));
+//! let mut bytes = responder.send(&lease.reply(&query)?)?;
+//! bytes.extend(responder.send(&done(&query, Code::Success, ""))?);
+//!
+//! requestor.receive(&bytes);
+//! assert!(matches!(requestor.poll()?, Some(RequestorEvent::Lease { .. })));
+//! assert!(matches!(
+//!     requestor.poll()?,
+//!     Some(RequestorEvent::Done { status: Code::Success, .. })
+//! ));
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 6926]: https://datatracker.ietf.org/doc/html/rfc6926
+use std::{collections::HashSet, fmt};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub use crate::framing::{encode_frame, FrameDecoder};
+use crate::{
+    error::{DecodeError, EncodeError},
+    v4::{
+        client_id::ClientId,
+        leasequery::LeaseQueryResult,
+        relay::{RelayAgentInformation, RelayCode, RelayInfo},
+        DhcpOption, HType, HardwareAddr, HwAddrError, Message, MessageType, Opcode, OptionCode,
+    },
+};
 
 /// Lease query data source flags
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -105,6 +156,15 @@ pub enum Code {
     QueryTerminated,
     MalformedQuery,
     NotAllowed,
+    /// the server can't send all catch-up data since the query start time
+    /// <https://datatracker.ietf.org/doc/html/rfc7724>
+    DataMissing,
+    /// the requestor already has an active leasequery connection
+    ConnectionActive,
+    /// the server sent all catch-up data, updates follow
+    CatchUpComplete,
+    /// the server won't negotiate TLS
+    TlsConnectionRefused,
     Unknown(u8),
 }
 
@@ -117,6 +177,10 @@ impl From<u8> for Code {
             2 => QueryTerminated,
             3 => MalformedQuery,
             4 => NotAllowed,
+            5 => DataMissing,
+            6 => ConnectionActive,
+            7 => CatchUpComplete,
+            8 => TlsConnectionRefused,
             _ => Unknown(n),
         }
     }
@@ -131,14 +195,295 @@ impl From<Code> for u8 {
             QueryTerminated => 2,
             MalformedQuery => 3,
             NotAllowed => 4,
+            DataMissing => 5,
+            ConnectionActive => 6,
+            CatchUpComplete => 7,
+            TlsConnectionRefused => 8,
             Unknown(code) => code,
         }
     }
 }
 
+/// Errors from a bulk leasequery connection
+#[derive(Error, Debug)]
+pub enum BulkQueryError {
+    /// a received message failed to decode
+    #[error("failed to decode message: {0}")]
+    Decode(#[from] DecodeError),
+    /// a message failed to encode
+    #[error("failed to encode message: {0}")]
+    Encode(#[from] EncodeError),
+    /// a message for a query that isn't in progress
+    #[error("no query in progress with xid {0:#x}")]
+    UnknownXid(u32),
+    /// a query was sent with the xid of one already in progress
+    #[error("a query with xid {0:#x} is already in progress")]
+    DuplicateXid(u32),
+    /// a message of a type that isn't expected at this point of the stream
+    #[error("unexpected message type {0:?}")]
+    UnexpectedMessage(Option<MessageType>),
+}
+
+/// What a bulk leasequery asks about. Query by IP address is not part of
+/// bulk leasequery, use [`crate::v4::leasequery`] instead
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BulkQuery {
+    /// leases for a MAC address, sent in htype/hlen/chaddr
+    HwAddr(HardwareAddr),
+    /// leases for a client identifier, sent in option 61
+    ClientId(ClientId),
+    /// leases relayed by a relay agent, sent in the relay-id (12) sub-option of option 82
+    RelayId(Vec<u8>),
+    /// leases with a remote-id, sent in the remote-id (2) sub-option of option 82
+    RemoteId(Vec<u8>),
+    /// every address the server is configured with
+    AllConfigured,
+}
+
+impl BulkQuery {
+    /// build a `DHCPBULKLEASEQUERY` with a random xid. Add Query Start/End
+    /// Time (154/155) options to limit the leases returned. Returns an error
+    /// if a queried hardware address doesn't fit in chaddr
+    pub fn to_message(&self) -> Result<Message, HwAddrError> {
+        let mut msg = Message::default();
+        msg.set_htype(HType::from(0));
+        let relay = |info| {
+            let mut relay = RelayAgentInformation::default();
+            relay.insert(info);
+            DhcpOption::RelayAgentInformation(relay)
+        };
+        match self {
+            BulkQuery::HwAddr(addr) => {
+                msg.set_hw_addr(addr)?;
+            }
+            BulkQuery::ClientId(id) => {
+                msg.opts_mut().insert(id.clone().into());
+            }
+            BulkQuery::RelayId(id) => {
+                msg.opts_mut().insert(relay(RelayInfo::RelayId(id.clone())));
+            }
+            BulkQuery::RemoteId(id) => {
+                msg.opts_mut()
+                    .insert(relay(RelayInfo::AgentRemoteId(id.clone())));
+            }
+            BulkQuery::AllConfigured => {}
+        }
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::BulkLeaseQuery));
+        msg.opts_mut().insert(DhcpOption::ParameterRequestList(vec![
+            OptionCode::AddressLeaseTime,
+            OptionCode::ClientIdentifier,
+            OptionCode::RelayAgentInformation,
+            OptionCode::ClientLastTransactionTime,
+            OptionCode::AssociatedIp,
+            OptionCode::BaseTime,
+            OptionCode::StartTimeOfState,
+            OptionCode::DhcpState,
+            OptionCode::DataSource,
+        ]));
+        Ok(msg)
+    }
+    /// parse a `DHCPBULKLEASEQUERY`. Returns `None` if `msg` isn't a bulk
+    /// leasequery or asks about more than one thing, which should be
+    /// answered with [`Code::MalformedQuery`]
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        if !msg.opts().has_msg_type(MessageType::BulkLeaseQuery) {
+            return None;
+        }
+        let mut queries = Vec::new();
+        if msg.hlen() != 0 {
+            queries.push(BulkQuery::HwAddr(msg.hw_addr().ok()?));
+        }
+        if let Some(id) = msg.opts().client_id() {
+            queries.push(BulkQuery::ClientId(id));
+        }
+        if let Some(DhcpOption::RelayAgentInformation(info)) =
+            msg.opts().get(OptionCode::RelayAgentInformation)
+        {
+            if let Some(RelayInfo::RelayId(id)) = info.get(RelayCode::RelayId) {
+                queries.push(BulkQuery::RelayId(id.clone()));
+            }
+            if let Some(RelayInfo::AgentRemoteId(id)) = info.get(RelayCode::AgentRemoteId) {
+                queries.push(BulkQuery::RemoteId(id.clone()));
+            }
+        }
+        match queries.len() {
+            0 => Some(BulkQuery::AllConfigured),
+            1 => queries.pop(),
+            _ => None,
+        }
+    }
+}
+
+/// build the `DHCPLEASEQUERYDONE` ending `query`. A status code option is
+/// included unless `status` is [`Code::Success`] with no message
+pub fn done(query: &Message, status: Code, message: &str) -> Message {
+    let mut msg = Message::default();
+    msg.set_opcode(Opcode::BootReply)
+        .set_xid(query.xid())
+        .set_htype(HType::from(0));
+    msg.opts_mut()
+        .insert(DhcpOption::MessageType(MessageType::LeaseQueryDone));
+    if status != Code::Success || !message.is_empty() {
+        msg.opts_mut().insert(DhcpOption::BulkLeaseQueryStatusCode(
+            status,
+            message.to_owned(),
+        ));
+    }
+    msg
+}
+
+/// the status code option of `msg`, if any
+pub(crate) fn status(msg: &Message) -> Option<(Code, &str)> {
+    match msg.opts().get(OptionCode::StatusCode)? {
+        DhcpOption::BulkLeaseQueryStatusCode(code, message) => Some((*code, message)),
+        _ => None,
+    }
+}
+
+/// Something received by a [`Requestor`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestorEvent {
+    /// a lease matching the query with `xid`. The full message is included
+    /// for bulk-only options like the DHCP state & base time
+    Lease {
+        /// xid of the query
+        xid: u32,
+        /// the lease
+        result: LeaseQueryResult,
+        /// the message the lease was read from
+        msg: Box<Message>,
+    },
+    /// the query with `xid` is finished, either by a `DHCPLEASEQUERYDONE` or
+    /// a reply with an unsuccessful status code
+    Done {
+        /// xid of the query
+        xid: u32,
+        /// status code, `Success` if the server didn't send one
+        status: Code,
+        /// status message
+        message: String,
+    },
+}
+
+/// The requestor end of a bulk leasequery connection
+#[derive(Debug, Default, Clone)]
+pub struct Requestor {
+    frames: FrameDecoder,
+    pending: HashSet<u32>,
+}
+
+impl Requestor {
+    /// create a requestor with no queries in progress
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// start a query, returns the bytes to write to the stream
+    pub fn send(&mut self, query: &Message) -> Result<Vec<u8>, BulkQueryError> {
+        if self.pending.contains(&query.xid()) {
+            return Err(BulkQueryError::DuplicateXid(query.xid()));
+        }
+        let frame = encode_frame(query)?;
+        self.pending.insert(query.xid());
+        Ok(frame)
+    }
+    /// add bytes read from the stream
+    pub fn receive(&mut self, bytes: &[u8]) {
+        self.frames.extend(bytes);
+    }
+    /// the next event from the received bytes, `None` if more are needed
+    pub fn poll(&mut self) -> Result<Option<RequestorEvent>, BulkQueryError> {
+        let msg = match self.frames.next_message::<Message>()? {
+            Some(msg) => msg,
+            None => return Ok(None),
+        };
+        let xid = msg.xid();
+        if !self.pending.contains(&xid) {
+            return Err(BulkQueryError::UnknownXid(xid));
+        }
+        let status = status(&msg).map(|(code, message)| (code, message.to_owned()));
+        if msg.opts().has_msg_type(MessageType::LeaseQueryDone)
+            || matches!(status, Some((code, _)) if code != Code::Success)
+        {
+            self.pending.remove(&xid);
+            let (status, message) = status.unwrap_or((Code::Success, String::new()));
+            return Ok(Some(RequestorEvent::Done {
+                xid,
+                status,
+                message,
+            }));
+        }
+        match LeaseQueryResult::from_message(&msg) {
+            Some(result) => Ok(Some(RequestorEvent::Lease {
+                xid,
+                result,
+                msg: Box::new(msg),
+            })),
+            None => Err(BulkQueryError::UnexpectedMessage(msg.opts().msg_type())),
+        }
+    }
+    /// true if any query is still waiting for a `DHCPLEASEQUERYDONE`
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+/// The server end of a bulk leasequery connection
+#[derive(Debug, Default, Clone)]
+pub struct Responder {
+    frames: FrameDecoder,
+    active: HashSet<u32>,
+}
+
+impl Responder {
+    /// create a responder with no queries in progress
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// add bytes read from the stream
+    pub fn receive(&mut self, bytes: &[u8]) {
+        self.frames.extend(bytes);
+    }
+    /// the next query from the received bytes, `None` if more are needed.
+    /// Use [`BulkQuery::from_message`] to find what it asks about
+    pub fn poll(&mut self) -> Result<Option<Message>, BulkQueryError> {
+        let msg = match self.frames.next_message::<Message>()? {
+            Some(msg) => msg,
+            None => return Ok(None),
+        };
+        if !self.active.insert(msg.xid()) {
+            return Err(BulkQueryError::DuplicateXid(msg.xid()));
+        }
+        Ok(Some(msg))
+    }
+    /// send a reply for a query in progress, returns the bytes to write to
+    /// the stream. Sending a `DHCPLEASEQUERYDONE` or a reply with an
+    /// unsuccessful status code finishes the query
+    pub fn send(&mut self, msg: &Message) -> Result<Vec<u8>, BulkQueryError> {
+        if !self.active.contains(&msg.xid()) {
+            return Err(BulkQueryError::UnknownXid(msg.xid()));
+        }
+        let frame = encode_frame(msg)?;
+        if msg.opts().has_msg_type(MessageType::LeaseQueryDone)
+            || matches!(status(msg), Some((code, _)) if code != Code::Success)
+        {
+            self.active.remove(&msg.xid());
+        }
+        Ok(frame)
+    }
+    /// true if any query hasn't been finished
+    pub fn is_active(&self) -> bool {
+        !self.active.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::v4::leasequery::LeaseRecord;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
     #[test]
     fn test_datasourceflags() {
@@ -151,4 +496,131 @@ mod tests {
         let flag = DataSourceFlags::new(0x80).set_remote();
         assert_eq!(flag.0, 0x81);
     }
+
+    #[test]
+    fn test_frames() -> Result<()> {
+        let first = BulkQuery::AllConfigured.to_message()?;
+        let second = BulkQuery::RelayId(vec![1, 2, 3]).to_message()?;
+        let mut bytes = encode_frame(&first)?;
+        let len = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+        assert_eq!(len, bytes.len() - 2);
+        bytes.extend(encode_frame(&second)?);
+
+        // split across reads
+        let mut frames = FrameDecoder::new();
+        frames.extend(&bytes[..1]);
+        assert_eq!(frames.next_frame(), None);
+        frames.extend(&bytes[1..len]);
+        assert!(frames.next_message::<Message>()?.is_none());
+        frames.extend(&bytes[len..]);
+        assert_eq!(frames.next_message()?, Some(first));
+        assert_eq!(frames.next_message()?, Some(second));
+        assert_eq!(frames.next_message::<Message>()?, None);
+        assert_eq!(frames.buffered(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_queries() -> Result<()> {
+        let mac: HardwareAddr = "de:ad:be:ef:00:01".parse()?;
+        for query in [
+            BulkQuery::HwAddr(mac),
+            BulkQuery::ClientId(ClientId::Opaque(vec![0, b'a'])),
+            BulkQuery::RelayId(vec![1, 2, 3]),
+            BulkQuery::RemoteId(vec![4, 5, 6]),
+            BulkQuery::AllConfigured,
+        ] {
+            let mut frames = FrameDecoder::new();
+            frames.extend(&encode_frame(&query.to_message()?)?);
+            let msg: Message = frames.next_message()?.unwrap();
+            assert_eq!(BulkQuery::from_message(&msg), Some(query));
+        }
+        // more than one query type is malformed
+        let mut msg = BulkQuery::RemoteId(vec![4, 5, 6]).to_message()?;
+        msg.opts_mut()
+            .insert(ClientId::Opaque(vec![0, b'a']).into());
+        assert_eq!(BulkQuery::from_message(&msg), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_session() -> Result<()> {
+        let mut requestor = Requestor::new();
+        let mut responder = Responder::new();
+
+        let first = BulkQuery::RelayId(vec![1, 2, 3]).to_message()?;
+        let mut second = BulkQuery::AllConfigured.to_message()?;
+        second.set_xid(first.xid().wrapping_add(1));
+        let mut bytes = requestor.send(&first)?;
+        bytes.extend(requestor.send(&second)?);
+        assert!(matches!(
+            requestor.send(&first),
+            Err(BulkQueryError::DuplicateXid(_))
+        ));
+
+        responder.receive(&bytes);
+        let first = responder.poll()?.unwrap();
+        let second = responder.poll()?.unwrap();
+        assert!(responder.poll()?.is_none());
+
+        let lease = LeaseQueryResult::Active(LeaseRecord::new("10.0.0.10".parse()?));
+        let mut reply = lease.reply(&first)?;
+        reply
+            .opts_mut()
+            .insert(DhcpOption::BulkLeaseQueryDhcpState(QueryState::Active));
+        let mut bytes = responder.send(&reply)?;
+        bytes.extend(responder.send(&done(&first, Code::Success, ""))?);
+        bytes.extend(responder.send(&done(&second, Code::NotAllowed, "go away"))?);
+        assert!(!responder.is_active());
+        assert!(matches!(
+            responder.send(&done(&first, Code::Success, "")),
+            Err(BulkQueryError::UnknownXid(_))
+        ));
+
+        // deliver one byte at a time
+        let mut events = Vec::new();
+        for b in bytes {
+            requestor.receive(&[b]);
+            while let Some(event) = requestor.poll()? {
+                events.push(event);
+            }
+        }
+        assert_eq!(events.len(), 3);
+        match &events[0] {
+            RequestorEvent::Lease { xid, result, msg } => {
+                assert_eq!(*xid, first.xid());
+                assert_eq!(result, &lease);
+                assert_eq!(
+                    msg.opts().get(OptionCode::DhcpState),
+                    Some(&DhcpOption::BulkLeaseQueryDhcpState(QueryState::Active))
+                );
+            }
+            event => panic!("unexpected {event:?}"),
+        }
+        assert_eq!(
+            events[1],
+            RequestorEvent::Done {
+                xid: first.xid(),
+                status: Code::Success,
+                message: String::new()
+            }
+        );
+        assert_eq!(
+            events[2],
+            RequestorEvent::Done {
+                xid: second.xid(),
+                status: Code::NotAllowed,
+                message: "go away".to_owned()
+            }
+        );
+        assert!(!requestor.is_pending());
+
+        // nothing in progress with this xid
+        requestor.receive(&encode_frame(&done(&first, Code::Success, ""))?);
+        assert!(matches!(
+            requestor.poll(),
+            Err(BulkQueryError::UnknownXid(_))
+        ));
+        Ok(())
+    }
 }
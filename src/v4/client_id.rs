@@ -0,0 +1,187 @@
+//! Typed client identifiers (option 61)
+//!
+//! [RFC 4361](https://datatracker.ietf.org/doc/html/rfc4361) lets a dual-stack
+//! node use the same DUID for DHCPv4 and DHCPv6, so a server can tie
+//! together the v4 & v6 leases belonging to one node.
+//!
+//! ```rust
+//! use dhcproto::{v4::{self, client_id::ClientId}, v6::{self, duid::Duid}};
+//! let duid = Duid::enterprise(1, &[1, 2, 3]);
+//! let iaid = 1;
+//! // derive both the v4 & v6 identifiers from one DUID
+//! let v4_id = ClientId::node(iaid, duid.clone());
+//! let v6_id = v4_id.v6_client_id().unwrap();
+//! assert_eq!(v6_id, v6::DhcpOption::ClientId(duid.as_ref().to_vec()));
+//!
+//! let mut opts = v4::DhcpOptions::new();
+//! opts.insert(v4_id.into());
+//! // server side, find the DUID to correlate with v6 leases
+//! assert_eq!(opts.client_id().unwrap().duid(), Some(&duid));
+//! ```
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    encoder::{Encodable, Encoder},
+    error::EncodeResult,
+    v4::{DhcpOption, HType},
+    v6::{self, duid::Duid},
+};
+
+/// type used for RFC 4361 node-specific client identifiers
+pub const NODE_SPECIFIC_TYPE: u8 = 255;
+
+/// A client identifier
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientId {
+    /// hardware type followed by hardware address
+    HwAddr {
+        /// hardware type
+        htype: HType,
+        /// hardware address
+        addr: Vec<u8>,
+    },
+    /// RFC 4361 node-specific identifier, type 255 followed by IAID & DUID
+    Node {
+        /// identity association id
+        iaid: u32,
+        /// DUID, the same one the node uses for DHCPv6
+        duid: Duid,
+    },
+    /// any other identifier, contains the full option data (including the type byte)
+    Opaque(Vec<u8>),
+}
+
+impl ClientId {
+    /// new hardware type + address identifier
+    pub fn hw_addr(htype: HType, addr: &[u8]) -> Self {
+        ClientId::HwAddr {
+            htype,
+            addr: addr.to_vec(),
+        }
+    }
+    /// new RFC 4361 node-specific identifier
+    pub fn node(iaid: u32, duid: Duid) -> Self {
+        ClientId::Node { iaid, duid }
+    }
+    /// parse the data of a client identifier option. Identifiers that
+    /// aren't a recognized format, including node-specific identifiers
+    /// without a DUID, are returned as `Opaque`
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        match bytes {
+            [NODE_SPECIFIC_TYPE, a, b, c, d, duid @ ..] if !duid.is_empty() => ClientId::Node {
+                iaid: u32::from_be_bytes([*a, *b, *c, *d]),
+                duid: Duid::unknown(duid),
+            },
+            // type 0 is used for non-hardware identifiers (ex. a fqdn)
+            [htype, addr @ ..] if *htype != 0 && *htype != NODE_SPECIFIC_TYPE => ClientId::HwAddr {
+                htype: (*htype).into(),
+                addr: addr.to_vec(),
+            },
+            _ => ClientId::Opaque(bytes.to_vec()),
+        }
+    }
+    /// encode the identifier to option data
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        // writing to a vec can't fail
+        self.encode(&mut Encoder::new(&mut buf)).unwrap();
+        buf
+    }
+    /// the IAID, if this is a node-specific identifier
+    pub fn iaid(&self) -> Option<u32> {
+        match self {
+            ClientId::Node { iaid, .. } => Some(*iaid),
+            _ => None,
+        }
+    }
+    /// the DUID, if this is a node-specific identifier. Compare against a
+    /// DHCPv6 ClientId to find the leases belonging to the same node
+    pub fn duid(&self) -> Option<&Duid> {
+        match self {
+            ClientId::Node { duid, .. } => Some(duid),
+            _ => None,
+        }
+    }
+    /// the DHCPv6 ClientId option for the same node, if this is a node-specific identifier
+    pub fn v6_client_id(&self) -> Option<v6::DhcpOption> {
+        self.duid()
+            .map(|duid| v6::DhcpOption::ClientId(duid.as_ref().to_vec()))
+    }
+    /// create a node-specific identifier from a DHCPv6 ClientId option.
+    /// returns `None` if `opt` is not a ClientId or its DUID is empty
+    pub fn from_v6_client_id(iaid: u32, opt: &v6::DhcpOption) -> Option<Self> {
+        match opt {
+            v6::DhcpOption::ClientId(duid) if !duid.is_empty() => {
+                Some(ClientId::node(iaid, duid.clone().into()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Encodable for ClientId {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        match self {
+            ClientId::HwAddr { htype, addr } => {
+                e.write_u8((*htype).into())?;
+                e.write_slice(addr)?;
+            }
+            ClientId::Node { iaid, duid } => {
+                e.write_u8(NODE_SPECIFIC_TYPE)?;
+                e.write_u32(*iaid)?;
+                e.write_slice(duid.as_ref())?;
+            }
+            ClientId::Opaque(bytes) => e.write_slice(bytes)?,
+        }
+        Ok(())
+    }
+}
+
+impl From<&[u8]> for ClientId {
+    fn from(bytes: &[u8]) -> Self {
+        ClientId::from_bytes(bytes)
+    }
+}
+
+impl From<ClientId> for DhcpOption {
+    fn from(id: ClientId) -> Self {
+        DhcpOption::ClientIdentifier(id.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_id() {
+        let bytes = [1, 0xde, 0xad, 0xbe, 0xef, 0x00, 0x01];
+        let id = ClientId::from_bytes(&bytes);
+        assert_eq!(id, ClientId::hw_addr(HType::Eth, &bytes[1..]));
+        assert_eq!(id.to_vec(), bytes);
+
+        let duid = Duid::enterprise(1, &[1, 2, 3]);
+        let id = ClientId::node(0xAABBCCDD, duid.clone());
+        let bytes = id.to_vec();
+        assert_eq!(&bytes[..5], &[255, 0xAA, 0xBB, 0xCC, 0xDD]);
+        assert_eq!(&bytes[5..], duid.as_ref());
+        assert_eq!(ClientId::from_bytes(&bytes), id);
+        assert_eq!(id.iaid(), Some(0xAABBCCDD));
+
+        let v6_id = id.v6_client_id().unwrap();
+        assert_eq!(ClientId::from_v6_client_id(0xAABBCCDD, &v6_id), Some(id));
+
+        // node-specific without a DUID
+        assert_eq!(
+            ClientId::from_v6_client_id(0xAABBCCDD, &v6::DhcpOption::ClientId(vec![])),
+            None
+        );
+        for bytes in [&[0, b'a', b'b'][..], &[255, 1, 2], &[255, 0, 0, 0, 1], &[]] {
+            let id = ClientId::from_bytes(bytes);
+            assert_eq!(id, ClientId::Opaque(bytes.to_vec()));
+            assert_eq!(id.to_vec(), bytes);
+        }
+    }
+}
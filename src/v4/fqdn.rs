@@ -154,6 +154,53 @@ impl FqdnFlags {
         *self = self.set_s(bit);
         self
     }
+    /// the flags a server should respond with, given the client's flags
+    /// (`self`) and the server's DNS update policy. Follows
+    /// [RFC 4702](https://datatracker.ietf.org/doc/html/rfc4702#section-3.1) for v4 and
+    /// [RFC 4704](https://datatracker.ietf.org/doc/html/rfc4704#section-4.1) for v6.
+    /// The E bit is kept from the client
+    ///
+    /// ```rust
+    /// # use dhcproto::v4::fqdn::{DdnsPolicy, FqdnFlags};
+    /// // client asks the server to update the forward record, but the server won't
+    /// let client = FqdnFlags::default().set_s(true);
+    /// let reply = client.server_response(DdnsPolicy::ClientUpdates);
+    /// assert!(!reply.s() && reply.o() && !reply.n());
+    /// ```
+    pub fn server_response(self, policy: DdnsPolicy) -> FqdnFlags {
+        let (n, s) = match policy {
+            DdnsPolicy::ClientChoice => (self.n(), !self.n() && self.s()),
+            DdnsPolicy::ServerUpdates => (false, true),
+            DdnsPolicy::ClientUpdates => (false, false),
+            DdnsPolicy::NoUpdates => (true, false),
+        };
+        // o is set when the server's s differs from what the client asked for
+        let o = if self.n() { s } else { s != self.s() };
+        FqdnFlags(self.0 & 0x04).set_n(n).set_s(s).set_o(o)
+    }
+    /// create flags from the v6 wire format, which has no E bit
+    /// <https://datatracker.ietf.org/doc/html/rfc4704#section-4.1>
+    pub(crate) fn from_v6(n: u8) -> Self {
+        FqdnFlags(((n & 0x04) << 1) | (n & 0x03))
+    }
+    /// convert to the v6 wire format
+    pub(crate) fn to_v6(self) -> u8 {
+        ((self.0 & 0x08) >> 1) | (self.0 & 0x03)
+    }
+}
+
+/// A server's policy for performing DNS updates on behalf of clients
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DdnsPolicy {
+    /// do what the client asks for
+    ClientChoice,
+    /// the server always updates the forward (A/AAAA) record
+    ServerUpdates,
+    /// the client always updates the forward record, the server only updates PTR
+    ClientUpdates,
+    /// the server performs no updates
+    NoUpdates,
 }
 
 impl From<u8> for FqdnFlags {
@@ -197,4 +244,43 @@ mod tests {
         let flag = flag.set_s(true);
         assert_eq!(flag.0, 0x05);
     }
+
+    #[test]
+    fn test_server_response() {
+        let client_s = FqdnFlags::default().set_s(true);
+        let client_n = FqdnFlags::default().set_n(true);
+        let client = FqdnFlags::default();
+
+        // server honors client
+        let r = client_s.server_response(DdnsPolicy::ClientChoice);
+        assert!(r.s() && !r.o() && !r.n());
+        let r = client_n.server_response(DdnsPolicy::ClientChoice);
+        assert!(!r.s() && !r.o() && r.n());
+        let r = client.server_response(DdnsPolicy::ClientChoice);
+        assert!(!r.s() && !r.o() && !r.n());
+
+        // server overrides the client
+        let r = client.server_response(DdnsPolicy::ServerUpdates);
+        assert!(r.s() && r.o() && !r.n());
+        let r = client_n.server_response(DdnsPolicy::ServerUpdates);
+        assert!(r.s() && r.o() && !r.n());
+        let r = client_s.server_response(DdnsPolicy::NoUpdates);
+        assert!(!r.s() && r.o() && r.n());
+        let r = client.server_response(DdnsPolicy::NoUpdates);
+        assert!(!r.s() && !r.o() && r.n());
+
+        // e bit is kept
+        let r = FqdnFlags::default()
+            .set_e(true)
+            .server_response(DdnsPolicy::ServerUpdates);
+        assert!(r.e());
+    }
+
+    #[test]
+    fn test_v6_flags() {
+        let flags = FqdnFlags::from_v6(0x05);
+        assert!(flags.n() && flags.s() && !flags.e());
+        assert_eq!(flags.to_v6(), 0x05);
+        assert_eq!(FqdnFlags::default().set_e(true).set_o(true).to_v6(), 0x02);
+    }
 }
@@ -55,6 +55,8 @@ pub enum HType {
     MAPOS,
     /// 26 Twinaxial
     Twinaxial,
+    /// 27 EUI-64
+    EUI64,
     /// 30 ARPSec
     ARPSec,
     /// 31 IPsec tunnel
@@ -95,6 +97,7 @@ impl From<u8> for HType {
             23 => Metricom,
             25 => MAPOS,
             26 => Twinaxial,
+            27 => EUI64,
             30 => ARPSec,
             31 => IPsecTunnel,
             32 => Infiniband,
@@ -131,6 +134,7 @@ impl From<HType> for u8 {
             Metricom => 23,
             MAPOS => 25,
             Twinaxial => 26,
+            EUI64 => 27,
             ARPSec => 30,
             IPsecTunnel => 31,
             Infiniband => 32,
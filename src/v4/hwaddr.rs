@@ -0,0 +1,248 @@
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::v4::HType;
+
+/// A hardware address, along with its hardware type
+///
+/// ```rust
+/// # use dhcproto::v4::{HardwareAddr, HType};
+/// let addr: HardwareAddr = "de:ad:be:ef:00:01".parse().unwrap();
+/// assert_eq!(addr.htype(), HType::Eth);
+/// assert_eq!(addr.to_string(), "de:ad:be:ef:00:01");
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HardwareAddr {
+    /// 48-bit ethernet MAC
+    Eui48([u8; 6]),
+    /// EUI-64
+    Eui64([u8; 8]),
+    /// 20 byte IP over InfiniBand link-layer address. Per
+    /// [RFC 4390](https://datatracker.ietf.org/doc/html/rfc4390#section-2.1)
+    /// this is not carried in chaddr, clients identify themselves with the
+    /// client identifier option instead
+    Infiniband([u8; 20]),
+    /// any other hardware type, created with [`HardwareAddr::new`]
+    Other(OtherHwAddr),
+}
+
+/// Address of a hardware type without its own [`HardwareAddr`] variant.
+/// Only [`HardwareAddr::new`] creates one, so the type is never one of the
+/// typed variants and the address is at most 255 bytes
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "OtherHwAddrRepr")
+)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OtherHwAddr {
+    htype: HType,
+    addr: Vec<u8>,
+}
+
+impl OtherHwAddr {
+    /// hardware type
+    pub fn htype(&self) -> HType {
+        self.htype
+    }
+    /// hardware address
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.addr
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct OtherHwAddrRepr {
+    htype: HType,
+    addr: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<OtherHwAddrRepr> for OtherHwAddr {
+    type Error = HwAddrError;
+
+    fn try_from(repr: OtherHwAddrRepr) -> Result<Self, Self::Error> {
+        match HardwareAddr::new(repr.htype, &repr.addr)? {
+            HardwareAddr::Other(addr) => Ok(addr),
+            _ => Err(HwAddrError::InvalidLength {
+                htype: repr.htype,
+                len: repr.addr.len(),
+            }),
+        }
+    }
+}
+
+/// Errors creating a [`HardwareAddr`]
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HwAddrError {
+    /// address length doesn't match what the hardware type requires
+    #[error("invalid address length {len} for hardware type {htype:?}")]
+    InvalidLength {
+        /// hardware type
+        htype: HType,
+        /// length of the address
+        len: usize,
+    },
+    /// the hardware type's address is not carried in chaddr
+    #[error("hardware type {0:?} does not use chaddr")]
+    NotInChaddr(HType),
+    /// address could not be parsed from a string
+    #[error("invalid hardware address string")]
+    Parse,
+}
+
+impl HardwareAddr {
+    /// create a new address, checking the length of `addr` against `htype`
+    pub fn new(htype: HType, addr: &[u8]) -> Result<Self, HwAddrError> {
+        let invalid = || HwAddrError::InvalidLength {
+            htype,
+            len: addr.len(),
+        };
+        Ok(match htype {
+            HType::Eth => HardwareAddr::Eui48(addr.try_into().map_err(|_| invalid())?),
+            HType::EUI64 => HardwareAddr::Eui64(addr.try_into().map_err(|_| invalid())?),
+            HType::Infiniband => HardwareAddr::Infiniband(addr.try_into().map_err(|_| invalid())?),
+            htype => {
+                if addr.len() > u8::MAX as usize {
+                    return Err(invalid());
+                }
+                HardwareAddr::Other(OtherHwAddr {
+                    htype,
+                    addr: addr.to_vec(),
+                })
+            }
+        })
+    }
+    /// the hardware type of this address
+    pub fn htype(&self) -> HType {
+        match self {
+            HardwareAddr::Eui48(_) => HType::Eth,
+            HardwareAddr::Eui64(_) => HType::EUI64,
+            HardwareAddr::Infiniband(_) => HType::Infiniband,
+            HardwareAddr::Other(other) => other.htype,
+        }
+    }
+    /// the address bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            HardwareAddr::Eui48(addr) => addr,
+            HardwareAddr::Eui64(addr) => addr,
+            HardwareAddr::Infiniband(addr) => addr,
+            HardwareAddr::Other(other) => &other.addr,
+        }
+    }
+    /// length of the address
+    pub fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+    /// is the address empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl AsRef<[u8]> for HardwareAddr {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl From<[u8; 6]> for HardwareAddr {
+    fn from(addr: [u8; 6]) -> Self {
+        HardwareAddr::Eui48(addr)
+    }
+}
+
+impl From<[u8; 8]> for HardwareAddr {
+    fn from(addr: [u8; 8]) -> Self {
+        HardwareAddr::Eui64(addr)
+    }
+}
+
+impl fmt::Display for HardwareAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.as_bytes().iter().enumerate() {
+            if i != 0 {
+                write!(f, ":")?;
+            }
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// parses `:` or `-` separated hex. The hardware type is picked from the
+/// length: 6 bytes is EUI-48, 8 is EUI-64 and 20 is InfiniBand
+impl FromStr for HardwareAddr {
+    type Err = HwAddrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let addr = s
+            .split([':', '-'])
+            .map(|b| match b.len() {
+                2 => u8::from_str_radix(b, 16).map_err(|_| HwAddrError::Parse),
+                _ => Err(HwAddrError::Parse),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let htype = match addr.len() {
+            6 => HType::Eth,
+            8 => HType::EUI64,
+            20 => HType::Infiniband,
+            _ => return Err(HwAddrError::Parse),
+        };
+        HardwareAddr::new(htype, &addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hwaddr() {
+        let addr = HardwareAddr::new(HType::Eth, &[1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(addr, HardwareAddr::Eui48([1, 2, 3, 4, 5, 6]));
+        assert_eq!(addr.to_string(), "01:02:03:04:05:06");
+        assert_eq!("01-02-03-04-05-06".parse::<HardwareAddr>(), Ok(addr));
+
+        assert_eq!(
+            HardwareAddr::new(HType::Eth, &[1, 2, 3]),
+            Err(HwAddrError::InvalidLength {
+                htype: HType::Eth,
+                len: 3
+            })
+        );
+        let addr: HardwareAddr = "00:11:22:33:44:55:66:77".parse().unwrap();
+        assert_eq!(addr.htype(), HType::EUI64);
+        let ib = [0xab; 20].map(|b| format!("{b:02x}")).join(":");
+        assert_eq!(
+            ib.parse::<HardwareAddr>().unwrap().htype(),
+            HType::Infiniband
+        );
+
+        assert!("01:02:03".parse::<HardwareAddr>().is_err());
+        assert!("01:02:03:04:05:zz".parse::<HardwareAddr>().is_err());
+        assert!("010:02:03:04:05:06".parse::<HardwareAddr>().is_err());
+
+        let addr = HardwareAddr::new(HType::IEEE802, &[1, 2, 3]).unwrap();
+        assert_eq!(addr.htype(), HType::IEEE802);
+        assert_eq!(addr.as_bytes(), &[1, 2, 3]);
+        assert!(HardwareAddr::new(HType::IEEE802, &[0; 256]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_hwaddr_json() {
+        let addr = HardwareAddr::new(HType::IEEE802, &[1, 2, 3]).unwrap();
+        let s = serde_json::to_string(&addr).unwrap();
+        assert_eq!(serde_json::from_str::<HardwareAddr>(&s).unwrap(), addr);
+        // an ethernet address must be the typed variant
+        let eth = r#"{"Other":{"htype":"Eth","addr":[1,2,3]}}"#;
+        assert!(serde_json::from_str::<HardwareAddr>(eth).is_err());
+    }
+}
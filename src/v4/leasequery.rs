@@ -0,0 +1,339 @@
+//! DHCPv4 leasequery ([RFC 4388])
+//!
+//! An access concentrator sends a `DHCPLEASEQUERY` to find the lease for an
+//! IP, MAC or client identifier. The server replies with `DHCPLEASEACTIVE`,
+//! `DHCPLEASEUNASSIGNED` (it owns the address but there is no active lease)
+//! or `DHCPLEASEUNKNOWN`.
+//!
+//! ```rust
+//! use dhcproto::v4::leasequery::{LeaseQuery, LeaseQueryResult, LeaseRecord};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // requestor
+//! let query = LeaseQuery::Ip("192.168.0.10".parse()?).to_message("192.168.0.1".parse()?)?;
+//!
+//! // server, look up the lease then reply
+//! let lease = match LeaseQuery::from_message(&query) {
+//!     Some(LeaseQuery::Ip(ip)) => LeaseRecord::new(ip),
+//!     _ => unreachable!(),
+//! };
+//! let reply = LeaseQueryResult::Active(lease.clone()).reply(&query)?;
+//!
+//! // requestor
+//! assert_eq!(LeaseQueryResult::from_message(&reply), Some(LeaseQueryResult::Active(lease)));
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 4388]: https://datatracker.ietf.org/doc/html/rfc4388
+use std::net::Ipv4Addr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::v4::{
+    client_id::ClientId, relay::RelayAgentInformation, DhcpOption, HType, HardwareAddr,
+    HwAddrError, Message, MessageType, Opcode, OptionCode,
+};
+
+/// What a leasequery asks about
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaseQuery {
+    /// query by IP address, sent in ciaddr
+    Ip(Ipv4Addr),
+    /// query by MAC address, sent in htype/hlen/chaddr
+    HwAddr(HardwareAddr),
+    /// query by client identifier, sent in option 61
+    ClientId(ClientId),
+}
+
+impl LeaseQuery {
+    /// build a `DHCPLEASEQUERY` with a random xid. `giaddr` is the
+    /// requestor's address, the server sends its reply there. The
+    /// parameter request list asks for the options a [`LeaseRecord`] holds.
+    /// Returns an error if a queried hardware address doesn't fit in chaddr
+    pub fn to_message(&self, giaddr: Ipv4Addr) -> Result<Message, HwAddrError> {
+        let mut msg = Message::default();
+        msg.set_giaddr(giaddr).set_htype(HType::from(0));
+        match self {
+            LeaseQuery::Ip(ip) => {
+                msg.set_ciaddr(*ip);
+            }
+            LeaseQuery::HwAddr(addr) => {
+                msg.set_hw_addr(addr)?;
+            }
+            LeaseQuery::ClientId(id) => {
+                msg.opts_mut().insert(id.clone().into());
+            }
+        }
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::LeaseQuery));
+        msg.opts_mut().insert(DhcpOption::ParameterRequestList(vec![
+            OptionCode::AddressLeaseTime,
+            OptionCode::ClientIdentifier,
+            OptionCode::ClientLastTransactionTime,
+            OptionCode::AssociatedIp,
+            OptionCode::RelayAgentInformation,
+        ]));
+        Ok(msg)
+    }
+    /// parse a `DHCPLEASEQUERY`. A non-zero ciaddr takes precedence, then
+    /// the client identifier, then chaddr. Returns `None` if `msg` isn't a
+    /// leasequery or doesn't query anything
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        if !msg.opts().has_msg_type(MessageType::LeaseQuery) {
+            return None;
+        }
+        if !msg.ciaddr().is_unspecified() {
+            Some(LeaseQuery::Ip(msg.ciaddr()))
+        } else if let Some(id) = msg.opts().client_id() {
+            Some(LeaseQuery::ClientId(id))
+        } else if msg.hlen() != 0 {
+            msg.hw_addr().ok().map(LeaseQuery::HwAddr)
+        } else {
+            None
+        }
+    }
+}
+
+/// A lease, as returned in a `DHCPLEASEACTIVE`
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaseRecord {
+    /// leased address. When a MAC or client identifier query matches several
+    /// leases, this is the most recent one
+    pub addr: Ipv4Addr,
+    /// client hardware address
+    pub hw_addr: Option<HardwareAddr>,
+    /// client identifier (61)
+    pub client_id: Option<ClientId>,
+    /// remaining lease time in seconds (51)
+    pub lease_time: Option<u32>,
+    /// seconds since the client last talked to the server (91)
+    pub last_transaction: Option<u32>,
+    /// all addresses leased to the client (92)
+    pub associated: Vec<Ipv4Addr>,
+    /// relay agent information from the client's last message (82)
+    pub relay_info: Option<RelayAgentInformation>,
+}
+
+impl LeaseRecord {
+    /// a lease for `addr` with no other information
+    pub fn new(addr: Ipv4Addr) -> Self {
+        Self {
+            addr,
+            hw_addr: None,
+            client_id: None,
+            lease_time: None,
+            last_transaction: None,
+            associated: Vec::new(),
+            relay_info: None,
+        }
+    }
+    /// read a lease from the header & options of a `DHCPLEASEACTIVE`
+    pub fn from_message(msg: &Message) -> Self {
+        let opts = msg.opts();
+        Self {
+            addr: msg.ciaddr(),
+            hw_addr: (msg.hlen() != 0).then(|| msg.hw_addr().ok()).flatten(),
+            client_id: opts.client_id(),
+            lease_time: match opts.get(OptionCode::AddressLeaseTime) {
+                Some(DhcpOption::AddressLeaseTime(secs)) => Some(*secs),
+                _ => None,
+            },
+            last_transaction: match opts.get(OptionCode::ClientLastTransactionTime) {
+                Some(DhcpOption::ClientLastTransactionTime(secs)) => Some(*secs),
+                _ => None,
+            },
+            associated: match opts.get(OptionCode::AssociatedIp) {
+                Some(DhcpOption::AssociatedIp(ips)) => ips.clone(),
+                _ => Vec::new(),
+            },
+            relay_info: match opts.get(OptionCode::RelayAgentInformation) {
+                Some(DhcpOption::RelayAgentInformation(info)) => Some(info.clone()),
+                _ => None,
+            },
+        }
+    }
+    /// write the lease to the header & options of `msg`. Returns an error
+    /// if the hardware address doesn't fit in chaddr
+    pub fn write_to(&self, msg: &mut Message) -> Result<(), HwAddrError> {
+        msg.set_ciaddr(self.addr);
+        if let Some(addr) = &self.hw_addr {
+            msg.set_hw_addr(addr)?;
+        }
+        let opts = msg.opts_mut();
+        if let Some(id) = &self.client_id {
+            opts.insert(id.clone().into());
+        }
+        if let Some(secs) = self.lease_time {
+            opts.insert(DhcpOption::AddressLeaseTime(secs));
+        }
+        if let Some(secs) = self.last_transaction {
+            opts.insert(DhcpOption::ClientLastTransactionTime(secs));
+        }
+        if !self.associated.is_empty() {
+            opts.insert(DhcpOption::AssociatedIp(self.associated.clone()));
+        }
+        if let Some(info) = &self.relay_info {
+            opts.insert(DhcpOption::RelayAgentInformation(info.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// The result of a leasequery
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaseQueryResult {
+    /// `DHCPLEASEACTIVE`, the client has an active lease
+    Active(LeaseRecord),
+    /// `DHCPLEASEUNASSIGNED`, the server is authoritative for the address but
+    /// it isn't leased
+    Unassigned(Ipv4Addr),
+    /// `DHCPLEASEUNKNOWN`, the server has no information
+    Unknown,
+}
+
+impl LeaseQueryResult {
+    /// the reply message type for this result
+    pub fn msg_type(&self) -> MessageType {
+        match self {
+            LeaseQueryResult::Active(_) => MessageType::LeaseActive,
+            LeaseQueryResult::Unassigned(_) => MessageType::LeaseUnassigned,
+            LeaseQueryResult::Unknown => MessageType::LeaseUnknown,
+        }
+    }
+    /// parse a leasequery reply. Returns `None` if `msg` isn't one
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        match msg.opts().msg_type()? {
+            MessageType::LeaseActive => {
+                Some(LeaseQueryResult::Active(LeaseRecord::from_message(msg)))
+            }
+            MessageType::LeaseUnassigned => Some(LeaseQueryResult::Unassigned(msg.ciaddr())),
+            MessageType::LeaseUnknown => Some(LeaseQueryResult::Unknown),
+            _ => None,
+        }
+    }
+    /// build the server's reply to `query`. The xid, flags & giaddr are
+    /// copied from the query. `DHCPLEASEUNASSIGNED` is only valid for a
+    /// query by IP, for other queries it is sent as `DHCPLEASEUNKNOWN`.
+    /// Returns an error if the lease's hardware address doesn't fit in chaddr
+    pub fn reply(&self, query: &Message) -> Result<Message, HwAddrError> {
+        let mut msg = Message::default();
+        msg.set_opcode(Opcode::BootReply)
+            .set_xid(query.xid())
+            .set_flags(query.flags())
+            .set_giaddr(query.giaddr())
+            .set_htype(query.htype())
+            .set_chaddr(query.chaddr());
+
+        let by_ip = !query.ciaddr().is_unspecified();
+        let msg_type = match self {
+            LeaseQueryResult::Active(lease) => {
+                lease.write_to(&mut msg)?;
+                MessageType::LeaseActive
+            }
+            LeaseQueryResult::Unassigned(ip) if by_ip => {
+                msg.set_ciaddr(*ip);
+                MessageType::LeaseUnassigned
+            }
+            LeaseQueryResult::Unassigned(_) | LeaseQueryResult::Unknown => {
+                msg.set_ciaddr(query.ciaddr());
+                MessageType::LeaseUnknown
+            }
+        };
+        msg.opts_mut().insert(DhcpOption::MessageType(msg_type));
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decoder::{Decodable, Decoder},
+        encoder::{Encodable, Encoder},
+        v4::relay::RelayInfo,
+    };
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn roundtrip(msg: &Message) -> Result<Message> {
+        let mut buf = Vec::new();
+        msg.encode(&mut Encoder::new(&mut buf))?;
+        Ok(Message::decode(&mut Decoder::new(&buf))?)
+    }
+
+    #[test]
+    fn test_leasequery_queries() -> Result<()> {
+        let giaddr: Ipv4Addr = "10.0.0.1".parse()?;
+        let mac: HardwareAddr = "de:ad:be:ef:00:01".parse()?;
+        let id = ClientId::hw_addr(HType::Eth, mac.as_bytes());
+        for query in [
+            LeaseQuery::Ip("10.0.0.10".parse()?),
+            LeaseQuery::HwAddr(mac.clone()),
+            LeaseQuery::ClientId(id.clone()),
+        ] {
+            let msg = roundtrip(&query.to_message(giaddr)?)?;
+            assert_eq!(msg.giaddr(), giaddr);
+            assert_eq!(msg.opcode(), Opcode::BootRequest);
+            assert_eq!(LeaseQuery::from_message(&msg), Some(query));
+        }
+
+        // ciaddr wins over the client id
+        let mut msg = LeaseQuery::ClientId(id).to_message(giaddr)?;
+        msg.set_ciaddr([10, 0, 0, 11]);
+        assert_eq!(
+            LeaseQuery::from_message(&msg),
+            Some(LeaseQuery::Ip("10.0.0.11".parse()?))
+        );
+        // nothing to query
+        let mut msg = Message::default();
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::LeaseQuery));
+        assert_eq!(LeaseQuery::from_message(&msg), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_leasequery_replies() -> Result<()> {
+        let giaddr: Ipv4Addr = "10.0.0.1".parse()?;
+        let mac: HardwareAddr = "de:ad:be:ef:00:01".parse()?;
+        let query = LeaseQuery::HwAddr(mac.clone()).to_message(giaddr)?;
+
+        let mut info = RelayAgentInformation::default();
+        info.insert(RelayInfo::AgentCircuitId(vec![1, 2, 3]));
+        let lease = LeaseRecord {
+            addr: "10.0.0.10".parse()?,
+            hw_addr: Some(mac),
+            client_id: Some(ClientId::Opaque(vec![0, b'a'])),
+            lease_time: Some(3600),
+            last_transaction: Some(30),
+            associated: vec!["10.0.0.10".parse()?, "10.0.0.12".parse()?],
+            relay_info: Some(info),
+        };
+        let reply = roundtrip(&LeaseQueryResult::Active(lease.clone()).reply(&query)?)?;
+        assert_eq!(reply.opcode(), Opcode::BootReply);
+        assert_eq!(reply.xid(), query.xid());
+        assert_eq!(reply.giaddr(), giaddr);
+        assert_eq!(
+            LeaseQueryResult::from_message(&reply),
+            Some(LeaseQueryResult::Active(lease))
+        );
+
+        // unassigned is only valid for queries by IP
+        let unassigned = LeaseQueryResult::Unassigned("10.0.0.20".parse()?);
+        let reply = roundtrip(&unassigned.reply(&query)?)?;
+        assert_eq!(
+            LeaseQueryResult::from_message(&reply),
+            Some(LeaseQueryResult::Unknown)
+        );
+        let query = LeaseQuery::Ip("10.0.0.20".parse()?).to_message(giaddr)?;
+        let reply = roundtrip(&unassigned.reply(&query)?)?;
+        assert_eq!(reply.opts().msg_type(), Some(MessageType::LeaseUnassigned));
+        assert_eq!(LeaseQueryResult::from_message(&reply), Some(unassigned));
+
+        assert_eq!(LeaseQueryResult::from_message(&query), None);
+        Ok(())
+    }
+}
@@ -83,16 +83,23 @@ use std::{fmt, net::Ipv4Addr, str::Utf8Error};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+pub mod active_query;
 pub mod bulk_query;
+pub mod client_id;
 mod flags;
 pub mod fqdn;
 mod htype;
+mod hwaddr;
+pub mod leasequery;
+pub mod msft;
 mod opcode;
 mod options;
 pub mod relay;
+mod route;
+pub mod sixrd;
 
 // re-export submodules from proto::msg
-pub use self::{flags::*, htype::*, opcode::*, options::*};
+pub use self::{flags::*, htype::*, hwaddr::*, opcode::*, options::*, route::*};
 pub use crate::{
     decoder::{Decodable, Decoder},
     encoder::{Encodable, Encoder},
@@ -282,7 +289,8 @@ impl Message {
 
     /// Get the message's chaddr.
     pub fn chaddr(&self) -> &[u8] {
-        &self.chaddr[..(self.hlen as usize)]
+        // hlen may be larger than chaddr in a decoded message
+        &self.chaddr[..(self.hlen as usize).min(16)]
     }
 
     /// Set the message's chaddr. `chaddr` can only up to 16 bytes in length
@@ -293,11 +301,58 @@ impl Message {
         } else {
             new_chaddr[..chaddr.len()].copy_from_slice(chaddr);
         }
-        self.hlen = chaddr.len() as u8;
+        self.hlen = chaddr.len().min(16) as u8;
         self.chaddr = new_chaddr;
         self
     }
 
+    /// Get the message's hardware address. Returns an error if `hlen` is not
+    /// valid for `htype`.
+    ///
+    /// InfiniBand addresses are not carried in chaddr ([RFC 4390]), so this
+    /// returns [`HwAddrError::NotInChaddr`] for them.
+    ///
+    /// [RFC 4390]: https://datatracker.ietf.org/doc/html/rfc4390#section-2.1
+    pub fn hw_addr(&self) -> Result<HardwareAddr, HwAddrError> {
+        if self.htype == HType::Infiniband {
+            return Err(HwAddrError::NotInChaddr(self.htype));
+        }
+        if self.hlen > 16 {
+            return Err(HwAddrError::InvalidLength {
+                htype: self.htype,
+                len: self.hlen as usize,
+            });
+        }
+        HardwareAddr::new(self.htype, self.chaddr())
+    }
+
+    /// Set the message's htype, hlen & chaddr from a hardware address.
+    ///
+    /// For InfiniBand, hlen & chaddr are set to 0 and the broadcast flag is set,
+    /// as required by [RFC 4390]. The address should be sent in the client
+    /// identifier option instead.
+    ///
+    /// Returns [`HwAddrError::InvalidLength`] if any other address is longer
+    /// than the 16 bytes of chaddr, the message is left unchanged.
+    ///
+    /// [RFC 4390]: https://datatracker.ietf.org/doc/html/rfc4390#section-2.1
+    pub fn set_hw_addr(&mut self, addr: &HardwareAddr) -> Result<&mut Self, HwAddrError> {
+        if let HardwareAddr::Infiniband(_) = addr {
+            self.hlen = 0;
+            self.chaddr = [0; 16];
+            self.flags = self.flags.set_broadcast();
+        } else if addr.len() > 16 {
+            return Err(HwAddrError::InvalidLength {
+                htype: addr.htype(),
+                len: addr.len(),
+            });
+        } else {
+            self.set_chaddr(addr.as_bytes());
+        }
+        self.htype = addr.htype();
+        Ok(self)
+    }
+
     /// Get the message's giaddr.
     /// Gateway IP
     pub fn giaddr(&self) -> Ipv4Addr {
@@ -462,28 +517,137 @@ impl Message {
     pub fn opts_mut(&mut self) -> &mut DhcpOptions {
         &mut self.opts
     }
+
+    /// The server the client should boot from. In order of preference:
+    ///
+    /// 1. the first address in TFTP server address (150)
+    /// 2. the `sname` header field
+    /// 3. TFTP server name (66)
+    /// 4. `siaddr`, if set
+    ///
+    /// `sname` is not used if it holds options (see [`OptionCode::OptionOverload`])
+    pub fn boot_server(&self) -> Option<BootServer> {
+        if let Some(DhcpOption::TftpServerAddress(ips)) =
+            self.opts.get(OptionCode::TftpServerAddress)
+        {
+            if let Some(ip) = ips.first() {
+                return Some(BootServer::Addr(*ip));
+            }
+        }
+        let name = self.sname().map(trim_nul).or_else(|| {
+            match self.opts.get(OptionCode::TftpServerName) {
+                Some(DhcpOption::TftpServerName(name)) => Some(trim_nul(name.as_bytes())),
+                _ => None,
+            }
+        });
+        match name {
+            Some(name) if !name.is_empty() => Some(
+                name.parse()
+                    .map_or_else(|_| BootServer::Name(name), BootServer::Addr),
+            ),
+            _ if !self.siaddr.is_unspecified() => Some(BootServer::Addr(self.siaddr)),
+            _ => None,
+        }
+    }
+
+    /// The file the client should boot. The `file` header field is used if
+    /// set, otherwise Bootfile name (67).
+    ///
+    /// `file` is not used if it holds options (see [`OptionCode::OptionOverload`])
+    pub fn boot_file(&self) -> Option<String> {
+        let file = self.fname().map(trim_nul).or_else(|| {
+            match self.opts.get(OptionCode::BootfileName) {
+                Some(DhcpOption::BootfileName(file)) => Some(trim_nul(file.as_bytes())),
+                _ => None,
+            }
+        })?;
+        (!file.is_empty()).then_some(file)
+    }
+}
+
+fn trim_nul(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// The server a client should boot from, see [`Message::boot_server`]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BootServer {
+    /// server address
+    Addr(Ipv4Addr),
+    /// server hostname
+    Name(String),
 }
 
 impl Decodable for Message {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let opcode = decoder.field("opcode", Opcode::decode)?;
+        let htype = decoder.field("htype", |d| d.read_u8())?.into();
+        let hlen = decoder.field("hlen", |d| d.read_u8())?;
+        let hops = decoder.field("hops", |d| d.read_u8())?;
+        let xid = decoder.field("xid", |d| d.read_u32())?;
+        let secs = decoder.field("secs", |d| d.read_u16())?;
+        let flags = decoder.field("flags", |d| d.read_u16())?.into();
+        let ciaddr = decoder.field("ciaddr", |d| d.read_u32())?.into();
+        let yiaddr = decoder.field("yiaddr", |d| d.read_u32())?.into();
+        let siaddr = decoder.field("siaddr", |d| d.read_u32())?.into();
+        let giaddr = decoder.field("giaddr", |d| d.read_u32())?.into();
+        let chaddr = decoder.field("chaddr", |d| d.read::<16>())?;
+        let mut sname_dec = decoder.field("sname", |d| d.sub_decoder(64))?;
+        let mut fname_dec = decoder.field("fname", |d| d.sub_decoder(128))?;
+        // TODO: check magic bytes against expected?
+        let magic = decoder.field("magic", |d| d.read::<4>())?;
+        let opts_region = (decoder.offset(), decoder.buffer());
+        let mut opts = decoder.field("opts", DhcpOptions::decode)?;
+
+        // if option overload is set, the file and/or sname fields hold
+        // more options: https://www.rfc-editor.org/rfc/rfc2131#section-4.1
+        let overload = match opts.get(OptionCode::OptionOverload) {
+            Some(DhcpOption::OptionOverload(n)) => *n,
+            _ => 0,
+        };
+        let mut fname = None;
+        let mut sname = None;
+        if overload & 0x03 != 0 {
+            // an option may be split across the fields, its parts are
+            // joined in the order options, file, sname (RFC 3396)
+            let mut regions = vec![opts_region];
+            if overload & 0x01 != 0 {
+                regions.push((fname_dec.offset(), fname_dec.buffer()));
+            }
+            if overload & 0x02 != 0 {
+                regions.push((sname_dec.offset(), sname_dec.buffer()));
+            }
+            opts = decoder.field("opts", |_| options::decode_regions(&regions))?;
+            // the options are encoded back into the options field, so the
+            // overload no longer applies
+            opts.remove(OptionCode::OptionOverload);
+        }
+        if overload & 0x01 == 0 {
+            fname = fname_dec.read_nul_bytes::<128>()?;
+        }
+        if overload & 0x02 == 0 {
+            sname = sname_dec.read_nul_bytes::<64>()?;
+        }
+
         Ok(Message {
-            opcode: Opcode::decode(decoder)?,
-            htype: decoder.read_u8()?.into(),
-            hlen: decoder.read_u8()?,
-            hops: decoder.read_u8()?,
-            xid: decoder.read_u32()?,
-            secs: decoder.read_u16()?,
-            flags: decoder.read_u16()?.into(),
-            ciaddr: decoder.read_u32()?.into(),
-            yiaddr: decoder.read_u32()?.into(),
-            siaddr: decoder.read_u32()?.into(),
-            giaddr: decoder.read_u32()?.into(),
-            chaddr: decoder.read::<16>()?,
-            sname: decoder.read_nul_bytes::<64>()?,
-            fname: decoder.read_nul_bytes::<128>()?,
-            // TODO: check magic bytes against expected?
-            magic: decoder.read::<4>()?,
-            opts: DhcpOptions::decode(decoder)?,
+            opcode,
+            htype,
+            hlen,
+            hops,
+            xid,
+            secs,
+            flags,
+            ciaddr,
+            yiaddr,
+            siaddr,
+            giaddr,
+            chaddr,
+            sname,
+            fname,
+            magic,
+            opts,
         })
     }
 }
@@ -568,6 +732,38 @@ mod tests {
         assert_eq!(msg, res);
         Ok(())
     }
+    #[test]
+    fn test_decode_error_context() -> Result<()> {
+        // truncated in the middle of chaddr
+        let err = Message::decode(&mut Decoder::new(&bootreq()[..36])).unwrap_err();
+        assert_eq!(err.offset(), Some(28));
+        assert_eq!(err.path().unwrap().to_string(), "chaddr");
+        assert!(matches!(err.kind(), DecodeError::NotEnoughBytes));
+
+        // subnet mask with a 2 byte value
+        let err = DhcpOption::decode(&mut Decoder::new(&[1, 2, 255, 255])).unwrap_err();
+        assert_eq!(err.offset(), Some(2));
+        assert_eq!(err.path().unwrap().to_string(), "[SubnetMask]");
+
+        // the same option in a whole message, after a message type
+        let mut msg = Message::default();
+        msg.opts_mut()
+            .insert(DhcpOption::MessageType(MessageType::Discover));
+        let mut buf = msg.to_vec()?;
+        let end = buf.len() - 1;
+        buf.splice(end.., [1, 2, 255, 255, 255]);
+        let err = Message::decode(&mut Decoder::new(&buf)).unwrap_err();
+        assert_eq!(err.offset(), Some(245));
+        assert_eq!(err.path().unwrap().to_string(), "opts[SubnetMask]");
+        assert!(matches!(err.kind(), DecodeError::NotEnoughBytes));
+
+        // a truncated last option is still ignored
+        buf.truncate(end + 3);
+        let msg = Message::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(msg.opts().get(OptionCode::SubnetMask), None);
+        Ok(())
+    }
+
     #[test]
     fn decode_offer() -> Result<()> {
         decode_ipv4(offer(), MessageType::Offer)?;
@@ -599,6 +795,134 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_hw_addr() {
+        let mut msg = Message::default();
+        let mac = HardwareAddr::Eui48([1, 2, 3, 4, 5, 6]);
+        msg.set_hw_addr(&mac).unwrap();
+        assert_eq!(msg.hlen(), 6);
+        assert_eq!(msg.hw_addr(), Ok(mac));
+
+        // hlen doesn't match htype
+        msg.set_chaddr(&[1, 2, 3]);
+        assert!(msg.hw_addr().is_err());
+
+        // too long for chaddr
+        let long = HardwareAddr::new(HType::IEEE802, &[0xaa; 17]).unwrap();
+        assert_eq!(
+            msg.set_hw_addr(&long).unwrap_err(),
+            HwAddrError::InvalidLength {
+                htype: HType::IEEE802,
+                len: 17
+            }
+        );
+        assert_eq!(msg.htype(), HType::Eth);
+        assert_eq!(msg.chaddr(), &[1, 2, 3]);
+
+        msg.set_hw_addr(&HardwareAddr::Infiniband([0xff; 20]))
+            .unwrap();
+        assert_eq!(msg.htype(), HType::Infiniband);
+        assert_eq!(msg.hlen(), 0);
+        assert!(msg.chaddr().is_empty());
+        assert!(msg.flags().broadcast());
+        assert_eq!(
+            msg.hw_addr(),
+            Err(HwAddrError::NotInChaddr(HType::Infiniband))
+        );
+    }
+
+    #[test]
+    fn test_boot_server_file() -> Result<()> {
+        let mut msg = Message::default();
+        assert_eq!(msg.boot_server(), None);
+        assert_eq!(msg.boot_file(), None);
+
+        msg.set_siaddr([10, 0, 0, 1]);
+        assert_eq!(
+            msg.boot_server(),
+            Some(BootServer::Addr([10, 0, 0, 1].into()))
+        );
+        msg.opts_mut()
+            .insert(DhcpOption::TftpServerName("tftp.local".into()));
+        assert_eq!(
+            msg.boot_server(),
+            Some(BootServer::Name("tftp.local".into()))
+        );
+        msg.set_sname_str("10.0.0.2");
+        assert_eq!(
+            msg.boot_server(),
+            Some(BootServer::Addr([10, 0, 0, 2].into()))
+        );
+        msg.opts_mut()
+            .insert(DhcpOption::TftpServerAddress(vec![[10, 0, 0, 3].into()]));
+        assert_eq!(
+            msg.boot_server(),
+            Some(BootServer::Addr([10, 0, 0, 3].into()))
+        );
+
+        msg.opts_mut()
+            .insert(DhcpOption::BootfileName("from-opt".into()));
+        assert_eq!(msg.boot_file(), Some("from-opt".into()));
+        msg.set_fname_str("from-header");
+        assert_eq!(msg.boot_file(), Some("from-header".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_option_overload() -> Result<()> {
+        let mut msg = Message::default();
+        msg.set_fname_str("ignored");
+        msg.opts_mut().insert(DhcpOption::OptionOverload(1));
+        let mut buf = msg.to_vec()?;
+        // file field holds options
+        let file_opts = [67, 4, b'b', b'o', b'o', b't', 255];
+        buf[108..108 + 128].fill(0);
+        buf[108..108 + file_opts.len()].copy_from_slice(&file_opts);
+
+        let msg = Message::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(msg.fname(), None);
+        assert_eq!(
+            msg.opts().get(OptionCode::BootfileName),
+            Some(&DhcpOption::BootfileName("boot".into()))
+        );
+        assert_eq!(msg.boot_file(), Some("boot".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_option_overload_split() -> Result<()> {
+        let mut msg = Message::default();
+        msg.opts_mut().insert(DhcpOption::OptionOverload(3));
+        msg.opts_mut().insert(DhcpOption::Hostname("abc".into()));
+        let mut buf = msg.to_vec()?;
+        // hostname continues in file then sname
+        let file_opts = [12, 2, b'd', b'e', 255];
+        buf[108..108 + file_opts.len()].copy_from_slice(&file_opts);
+        let sname_opts = [0, 12, 1, b'f', 67, 4, b'b', b'o', b'o', b't', 255];
+        buf[44..44 + sname_opts.len()].copy_from_slice(&sname_opts);
+
+        let msg = Message::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(
+            msg.opts().get(OptionCode::Hostname),
+            Some(&DhcpOption::Hostname("abcdef".into()))
+        );
+        assert_eq!(msg.boot_file(), Some("boot".into()));
+        assert_eq!((msg.fname(), msg.sname()), (None, None));
+        assert_eq!(msg.opts().get(OptionCode::OptionOverload), None);
+
+        // everything is encoded back into the options field
+        let decoded = Message::decode(&mut Decoder::new(&msg.to_vec()?))?;
+        assert_eq!(decoded, msg);
+
+        // errors point into the field holding the malformed option
+        let sname_opts = [0, 1, 2, 255, 255, 255];
+        buf[44..44 + sname_opts.len()].copy_from_slice(&sname_opts);
+        let err = Message::decode(&mut Decoder::new(&buf)).unwrap_err();
+        assert_eq!(err.offset(), Some(47));
+        assert_eq!(err.path().unwrap().to_string(), "opts[SubnetMask]");
+        Ok(())
+    }
+
     #[test]
     fn test_set_chaddr() -> Result<()> {
         let mut msg = Message::new(
@@ -0,0 +1,255 @@
+//! Microsoft vendor specific options
+//!
+//! Windows clients send a vendor class identifier (option 60) of `MSFT 5.0`,
+//! the vendor extensions option (43) is then encoded as sub-options defined
+//! in [MS-DHCPE](https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-dhcpe/).
+//! Server replies don't repeat the vendor class, so a client decodes the
+//! option 43 data with [`MsftVendorOptions::from_bytes`], while a server can
+//! use [`MsftVendorOptions::from_opts`] to check the client's class first.
+//!
+//! ```rust
+//! use dhcproto::v4::{self, msft::{MsftOption, MsftVendorOptions}};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // server
+//! let mut msft = MsftVendorOptions::default();
+//! msft.insert(MsftOption::DisableNetbios(true));
+//! let mut ack = v4::DhcpOptions::new();
+//! ack.insert(msft.to_opt()?);
+//!
+//! // client
+//! if let Some(v4::DhcpOption::VendorExtensions(data)) = ack.get(v4::OptionCode::VendorExtensions) {
+//!     assert_eq!(
+//!         MsftVendorOptions::from_bytes(data)?.get(1),
+//!         Some(&MsftOption::DisableNetbios(true))
+//!     );
+//! }
+//! # Ok(()) }
+//! ```
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::{Encodable, Encoder},
+    error::{DecodeResult, EncodeError, EncodeResult},
+    v4::{DhcpOption, DhcpOptions, OptionCode},
+};
+
+/// vendor class identifier sent by Windows clients
+pub const VENDOR_CLASS: &[u8] = b"MSFT 5.0";
+
+const NETBIOS_ENABLED: u32 = 1;
+const NETBIOS_DISABLED: u32 = 2;
+
+/// Microsoft vendor extensions, carried in option 43
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MsftVendorOptions(Vec<MsftOption>);
+
+impl MsftVendorOptions {
+    /// decode the data of a vendor extensions option (43)
+    pub fn from_bytes(data: &[u8]) -> DecodeResult<Self> {
+        Self::decode(&mut Decoder::new(data))
+    }
+    /// decode the vendor extensions in a client's `opts`. Returns `Ok(None)`
+    /// if there is no vendor extensions option or the vendor class isn't
+    /// `MSFT 5.0`
+    pub fn from_opts(opts: &DhcpOptions) -> DecodeResult<Option<Self>> {
+        match (
+            opts.get(OptionCode::ClassIdentifier),
+            opts.get(OptionCode::VendorExtensions),
+        ) {
+            (
+                Some(DhcpOption::ClassIdentifier(class)),
+                Some(DhcpOption::VendorExtensions(data)),
+            ) if class == VENDOR_CLASS => Ok(Some(Self::from_bytes(data)?)),
+            _ => Ok(None),
+        }
+    }
+    /// encode as a vendor extensions option (43). Fails if an unknown
+    /// sub-option has more than 255 bytes of data
+    pub fn to_opt(&self) -> EncodeResult<DhcpOption> {
+        let mut buf = Vec::new();
+        self.encode(&mut Encoder::new(&mut buf))?;
+        Ok(DhcpOption::VendorExtensions(buf))
+    }
+    /// get the first sub-option with `code`
+    pub fn get(&self, code: u8) -> Option<&MsftOption> {
+        self.0.iter().find(|opt| opt.code() == code)
+    }
+    /// insert a sub-option, replacing any existing one with the same code
+    pub fn insert(&mut self, opt: MsftOption) -> Option<MsftOption> {
+        match self.0.iter_mut().find(|o| o.code() == opt.code()) {
+            Some(existing) => Some(std::mem::replace(existing, opt)),
+            None => {
+                self.0.push(opt);
+                None
+            }
+        }
+    }
+    /// iterate over sub-options
+    pub fn iter(&self) -> impl Iterator<Item = &MsftOption> {
+        self.0.iter()
+    }
+    /// Returns `true` if there are no sub-options
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Decodable for MsftVendorOptions {
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let mut opts = Vec::new();
+        while !decoder.buffer().is_empty() {
+            opts.push(MsftOption::decode(decoder)?);
+        }
+        Ok(MsftVendorOptions(opts))
+    }
+}
+
+impl Encodable for MsftVendorOptions {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        self.0.iter().try_for_each(|opt| opt.encode(e))
+    }
+}
+
+/// A Microsoft vendor sub-option
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MsftOption {
+    /// 1 disable NetBIOS over TCP/IP, sent as 2 to disable & 1 to enable.
+    /// Other values decode as `Unknown`
+    DisableNetbios(bool),
+    /// 2 release the lease on shutdown
+    ReleaseOnShutdown(bool),
+    /// 3 default router metric base
+    DefaultRouterMetricBase(u32),
+    /// unknown sub-option
+    Unknown(u8, Vec<u8>),
+}
+
+impl MsftOption {
+    /// the sub-option code
+    pub fn code(&self) -> u8 {
+        match self {
+            MsftOption::DisableNetbios(_) => 1,
+            MsftOption::ReleaseOnShutdown(_) => 2,
+            MsftOption::DefaultRouterMetricBase(_) => 3,
+            MsftOption::Unknown(code, _) => *code,
+        }
+    }
+}
+
+impl Decodable for MsftOption {
+    fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
+        let code = decoder.read_u8()?;
+        let len = decoder.read_u8()? as usize;
+        let mut dec = decoder.sub_decoder(len)?;
+        Ok(match (code, len) {
+            (1, 4) => match dec.read_u32()? {
+                NETBIOS_ENABLED => MsftOption::DisableNetbios(false),
+                NETBIOS_DISABLED => MsftOption::DisableNetbios(true),
+                n => MsftOption::Unknown(code, n.to_be_bytes().to_vec()),
+            },
+            (2, 4) => MsftOption::ReleaseOnShutdown(dec.read_u32()? == 1),
+            (3, 4) => MsftOption::DefaultRouterMetricBase(dec.read_u32()?),
+            (code, len) => MsftOption::Unknown(code, dec.read_slice(len)?.to_vec()),
+        })
+    }
+}
+
+impl Encodable for MsftOption {
+    fn encode(&self, e: &mut Encoder<'_>) -> EncodeResult<()> {
+        e.write_u8(self.code())?;
+        match self {
+            MsftOption::DisableNetbios(b) => {
+                e.write_u8(4)?;
+                e.write_u32(if *b {
+                    NETBIOS_DISABLED
+                } else {
+                    NETBIOS_ENABLED
+                })?;
+            }
+            MsftOption::ReleaseOnShutdown(b) => {
+                e.write_u8(4)?;
+                e.write_u32(*b as u32)?;
+            }
+            MsftOption::DefaultRouterMetricBase(n) => {
+                e.write_u8(4)?;
+                e.write_u32(*n)?;
+            }
+            MsftOption::Unknown(_, data) => {
+                let len = u8::try_from(data.len())
+                    .map_err(|_| EncodeError::OptionTooLarge { len: data.len() })?;
+                e.write_u8(len)?;
+                e.write_slice(data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    #[test]
+    fn test_msft_opts() -> Result<()> {
+        let mut msft = MsftVendorOptions::default();
+        msft.insert(MsftOption::DisableNetbios(true));
+        msft.insert(MsftOption::ReleaseOnShutdown(false));
+        msft.insert(MsftOption::DefaultRouterMetricBase(10));
+        msft.insert(MsftOption::Unknown(5, vec![1, 2]));
+        let opt = msft.to_opt()?;
+        assert_eq!(
+            opt,
+            DhcpOption::VendorExtensions(vec![
+                1, 4, 0, 0, 0, 2, 2, 4, 0, 0, 0, 0, 3, 4, 0, 0, 0, 10, 5, 2, 1, 2
+            ])
+        );
+
+        let mut opts = DhcpOptions::new();
+        opts.insert(opt);
+        // no vendor class
+        assert_eq!(MsftVendorOptions::from_opts(&opts)?, None);
+        opts.insert(DhcpOption::ClassIdentifier(VENDOR_CLASS.to_vec()));
+        assert_eq!(MsftVendorOptions::from_opts(&opts)?, Some(msft));
+        Ok(())
+    }
+
+    #[test]
+    fn test_netbios() -> Result<()> {
+        // option 43 from a Windows DHCP server with NetBIOS disabled
+        let data = [0x01, 0x04, 0x00, 0x00, 0x00, 0x02];
+        let msft = MsftVendorOptions::from_bytes(&data)?;
+        assert_eq!(msft.get(1), Some(&MsftOption::DisableNetbios(true)));
+        assert_eq!(msft.to_opt()?, DhcpOption::VendorExtensions(data.to_vec()));
+
+        let enabled = MsftVendorOptions::from_bytes(&[1, 4, 0, 0, 0, 1])?;
+        assert_eq!(enabled.get(1), Some(&MsftOption::DisableNetbios(false)));
+        let mut buf = Vec::new();
+        MsftOption::DisableNetbios(false).encode(&mut Encoder::new(&mut buf))?;
+        assert_eq!(buf, [1, 4, 0, 0, 0, 1]);
+
+        // values other than 1 & 2 are kept as they are
+        let other = MsftVendorOptions::from_bytes(&[1, 4, 0, 0, 0, 0])?;
+        assert_eq!(other.get(1), Some(&MsftOption::Unknown(1, vec![0; 4])));
+        assert_eq!(
+            other.to_opt()?,
+            DhcpOption::VendorExtensions(vec![1, 4, 0, 0, 0, 0])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_too_large() {
+        let mut msft = MsftVendorOptions::default();
+        msft.insert(MsftOption::Unknown(5, vec![0; 256]));
+        assert!(matches!(
+            msft.to_opt(),
+            Err(EncodeError::OptionTooLarge { len: 256 })
+        ));
+    }
+}
@@ -1,20 +1,25 @@
-use std::{borrow::Cow, collections::HashMap, iter, net::Ipv4Addr};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    iter,
+    net::{Ipv4Addr, Ipv6Addr},
+};
 
 use crate::Domain;
 use crate::{
     decoder::{Decodable, Decoder},
     encoder::{Encodable, Encoder},
-    error::{DecodeResult, EncodeResult},
+    error::{DecodeError, DecodeResult, EncodeResult, PathSegment},
     v4::bulk_query,
-    v4::{fqdn, relay},
+    v4::{client_id, fqdn, relay, sixrd},
 };
 
-use ipnet::Ipv4Net;
+use ipnet::{Ipv4Net, Ipv6Net};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use trust_dns_proto::{
     rr::Name,
-    serialize::binary::{BinDecodable, BinDecoder, BinEncodable, BinEncoder, EncodeMode},
+    serialize::binary::{BinDecodable, BinDecoder, BinEncoder, EncodeMode},
 };
 
 /// Options for DHCP. This implemention of options ignores PAD bytes.
@@ -106,6 +111,15 @@ impl DhcpOptions {
             _ => unreachable!("cannot return different option for MessageType"),
         }
     }
+    /// return the client identifier, parsed as a [`ClientId`]
+    ///
+    /// [`ClientId`]: crate::v4::client_id::ClientId
+    pub fn client_id(&self) -> Option<client_id::ClientId> {
+        match self.get(OptionCode::ClientIdentifier)? {
+            DhcpOption::ClientIdentifier(id) => Some(client_id::ClientId::from_bytes(id)),
+            _ => unreachable!("cannot return different option for ClientIdentifier"),
+        }
+    }
     /// determine if options contains a specific message type
     /// ```
     /// # use dhcproto::v4::{MessageType, DhcpOption, DhcpOptions};
@@ -187,9 +201,15 @@ impl Decodable for DhcpOptions {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         // represented as a vector in the actual message
         let mut opts = HashMap::new();
-        // should we error the whole parser if we fail to parse an
-        // option or just stop parsing options? -- here we will just stop
-        while let Ok(opt) = DhcpOption::decode(decoder) {
+        loop {
+            let opt = match DhcpOption::decode(decoder) {
+                Ok(opt) => opt,
+                // a malformed option body is an error, errors from the
+                // option body have a location attached
+                Err(err) if err.offset().is_some() => return Err(err),
+                // ran out of bytes or the last option is truncated, stop here
+                Err(_) => break,
+            };
             // we throw away PAD bytes here
             match opt {
                 DhcpOption::End => {
@@ -353,6 +373,10 @@ pub enum OptionCode {
     ClientIdentifier,
     /// 65 NIS-Server-Addr
     NISServerAddr,
+    /// 66 TFTP server name - <https://www.rfc-editor.org/rfc/rfc2132.html#section-9.4>
+    TftpServerName,
+    /// 67 Bootfile name - <https://www.rfc-editor.org/rfc/rfc2132.html#section-9.5>
+    BootfileName,
     /// 80 Rapid Commit - <https://www.rfc-editor.org/rfc/rfc4039.html>
     RapidCommit,
     /// 81 FQDN - <https://datatracker.ietf.org/doc/html/rfc4702>
@@ -375,8 +399,12 @@ pub enum OptionCode {
     SubnetSelection,
     /// 119 Domain Search - <https://www.rfc-editor.org/rfc/rfc3397.html>
     DomainSearch,
+    /// 120 SIP Servers - <https://www.rfc-editor.org/rfc/rfc3361.html>
+    SipServers,
     /// 121 Classless Static Route - <https://www.rfc-editor.org/rfc/rfc3442>
     ClasslessStaticRoute,
+    /// 150 TFTP server address - <https://www.rfc-editor.org/rfc/rfc5859.html>
+    TftpServerAddress,
     /// 151 status-code - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.2>
     StatusCode,
     /// 152 - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.3>
@@ -391,6 +419,12 @@ pub enum OptionCode {
     DhcpState,
     /// 157 - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.8>
     DataSource,
+    /// 212 6rd - <https://www.rfc-editor.org/rfc/rfc5969.html#section-7.1.1>
+    SixRd,
+    /// 249 Microsoft Classless Static Route, same format as 121
+    MsClasslessStaticRoute,
+    /// 252 Web Proxy Auto-Discovery URL
+    Wpad,
     /// Unknown option
     Unknown(u8),
     /// 255 End
@@ -472,6 +506,8 @@ impl From<u8> for OptionCode {
             60 => ClassIdentifier,
             61 => ClientIdentifier,
             65 => NISServerAddr,
+            66 => TftpServerName,
+            67 => BootfileName,
             80 => RapidCommit,
             81 => ClientFQDN,
             82 => RelayAgentInformation,
@@ -483,7 +519,9 @@ impl From<u8> for OptionCode {
             114 => CaptivePortal,
             118 => SubnetSelection,
             119 => DomainSearch,
+            120 => SipServers,
             121 => ClasslessStaticRoute,
+            150 => TftpServerAddress,
             151 => StatusCode,
             152 => BaseTime,
             153 => StartTimeOfState,
@@ -491,6 +529,9 @@ impl From<u8> for OptionCode {
             155 => QueryEndTime,
             156 => DhcpState,
             157 => DataSource,
+            212 => SixRd,
+            249 => MsClasslessStaticRoute,
+            252 => Wpad,
             255 => End,
             // TODO: implement more
             n => Unknown(n),
@@ -561,6 +602,8 @@ impl From<OptionCode> for u8 {
             ClassIdentifier => 60,
             ClientIdentifier => 61,
             NISServerAddr => 65,
+            TftpServerName => 66,
+            BootfileName => 67,
             RapidCommit => 80,
             ClientFQDN => 81,
             RelayAgentInformation => 82,
@@ -572,7 +615,9 @@ impl From<OptionCode> for u8 {
             CaptivePortal => 114,
             SubnetSelection => 118,
             DomainSearch => 119,
+            SipServers => 120,
             ClasslessStaticRoute => 121,
+            TftpServerAddress => 150,
             StatusCode => 151,
             BaseTime => 152,
             StartTimeOfState => 153,
@@ -580,6 +625,9 @@ impl From<OptionCode> for u8 {
             QueryEndTime => 155,
             DhcpState => 156,
             DataSource => 157,
+            SixRd => 212,
+            MsClasslessStaticRoute => 249,
+            Wpad => 252,
             End => 255,
             // TODO: implement more
             Unknown(n) => n,
@@ -714,6 +762,10 @@ pub enum DhcpOption {
     ClientIdentifier(Vec<u8>),
     /// 65 NIS-Server-Addr
     NISServerAddr(Vec<Ipv4Addr>),
+    /// 66 TFTP server name - <https://www.rfc-editor.org/rfc/rfc2132.html#section-9.4>
+    TftpServerName(String),
+    /// 67 Bootfile name - <https://www.rfc-editor.org/rfc/rfc2132.html#section-9.5>
+    BootfileName(String),
     /// 80 Rapid Commit - <https://www.rfc-editor.org/rfc/rfc4039.html>
     RapidCommit,
     /// 81 FQDN - <https://datatracker.ietf.org/doc/html/rfc4702>
@@ -736,8 +788,12 @@ pub enum DhcpOption {
     SubnetSelection(Ipv4Addr),
     /// 119 Domain Search - <https://www.rfc-editor.org/rfc/rfc3397.html>
     DomainSearch(Vec<Domain>),
+    /// 120 SIP Servers - <https://www.rfc-editor.org/rfc/rfc3361.html>
+    SipServers(SipServers),
     /// 121 Classless Static Route - <https://www.rfc-editor.org/rfc/rfc3442>
     ClasslessStaticRoute(Vec<(Ipv4Net, Ipv4Addr)>),
+    /// 150 TFTP server address - <https://www.rfc-editor.org/rfc/rfc5859.html>
+    TftpServerAddress(Vec<Ipv4Addr>),
     /// 151 status-code - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.2>
     BulkLeaseQueryStatusCode(bulk_query::Code, String),
     /// 152 - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.3>
@@ -752,6 +808,13 @@ pub enum DhcpOption {
     BulkLeaseQueryDhcpState(bulk_query::QueryState),
     /// 157 - <https://www.rfc-editor.org/rfc/rfc6926.html#section-6.2.8>
     BulkLeaseQueryDataSource(bulk_query::DataSourceFlags),
+    /// 212 6rd - <https://www.rfc-editor.org/rfc/rfc5969.html#section-7.1.1>
+    SixRd(sixrd::SixRd),
+    /// 249 Microsoft Classless Static Route, same format as 121
+    MsClasslessStaticRoute(Vec<(Ipv4Net, Ipv4Addr)>),
+    /// 252 Web Proxy Auto-Discovery URL. Kept as a string, some servers
+    /// include a trailing NUL which would not parse as a URL
+    Wpad(String),
     /// Unknown option
     Unknown(UnknownOption),
     /// 255 End
@@ -836,6 +899,16 @@ impl From<Architecture> for u16 {
     }
 }
 
+/// SIP servers, either as a list of domain names or IPv4 addresses
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SipServers {
+    /// enc 0, list of domain names
+    Domains(Vec<Domain>),
+    /// enc 1, list of IPv4 addresses
+    Addrs(Vec<Ipv4Addr>),
+}
+
 /// NetBIOS allows several different node types
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -924,6 +997,9 @@ fn decode_inner(
         OptionCode::TcpKeepaliveGarbage => TcpKeepaliveGarbage(decoder.read_bool()?),
         OptionCode::NISDomain => NISDomain(decoder.read_string(len)?),
         OptionCode::NISServerAddr => NISServerAddr(decoder.read_ipv4s(len)?),
+        OptionCode::TftpServerName => TftpServerName(decoder.read_string(len)?),
+        OptionCode::BootfileName => BootfileName(decoder.read_string(len)?),
+        OptionCode::TftpServerAddress => TftpServerAddress(decoder.read_ipv4s(len)?),
         OptionCode::NIS => NIS(decoder.read_ipv4s(len)?),
         OptionCode::NTPServers => NTPServers(decoder.read_ipv4s(len)?),
         OptionCode::VendorExtensions => VendorExtensions(decoder.read_slice(len)?.to_vec()),
@@ -958,7 +1034,7 @@ fn decode_inner(
             RapidCommit
         }
         OptionCode::RelayAgentInformation => {
-            let mut dec = Decoder::new(decoder.read_slice(len)?);
+            let mut dec = decoder.sub_decoder(len)?;
             RelayAgentInformation(relay::RelayAgentInformation::decode(&mut dec)?)
         }
         OptionCode::ClientLastTransactionTime => ClientLastTransactionTime(decoder.read_u32()?),
@@ -976,15 +1052,23 @@ fn decode_inner(
         }
         OptionCode::CaptivePortal => CaptivePortal(decoder.read_str(len)?.parse()?),
         OptionCode::SubnetSelection => SubnetSelection(decoder.read_ipv4(len)?),
-        OptionCode::DomainSearch => {
-            let mut name_decoder = BinDecoder::new(decoder.read_slice(len)?);
-            let mut names = Vec::new();
-            while let Ok(name) = Name::read(&mut name_decoder) {
-                names.push(Domain(name));
+        OptionCode::DomainSearch => DomainSearch(Domain::decode_list(decoder.read_slice(len)?)),
+        OptionCode::SipServers => match decoder.peek_u8()? {
+            0 => {
+                let _enc = decoder.read_u8()?;
+                let names = Domain::decode_list(decoder.read_slice(len - 1)?);
+                SipServers(self::SipServers::Domains(names))
             }
-
-            DomainSearch(names)
-        }
+            1 => {
+                let _enc = decoder.read_u8()?;
+                SipServers(self::SipServers::Addrs(decoder.read_ipv4s(len - 1)?))
+            }
+            // unknown encoding, keep the raw data
+            _ => Unknown(UnknownOption {
+                code: code.into(),
+                data: decoder.read_slice(len)?.to_vec(),
+            }),
+        },
         OptionCode::StatusCode => {
             let code = decoder.read_u8()?.into();
             // len - 1 because code is included in length
@@ -1011,6 +1095,17 @@ fn decode_inner(
         OptionCode::DataSource => {
             BulkLeaseQueryDataSource(bulk_query::DataSourceFlags::new(decoder.read_u8()?))
         }
+        OptionCode::SixRd => {
+            let mut decoder = decoder.sub_decoder(len)?;
+            let ipv4_mask_len = decoder.read_u8()?;
+            let prefix_len = decoder.read_u8()?;
+            let prefix = Ipv6Addr::from(decoder.read::<16>()?);
+            let prefix = Ipv6Net::new(prefix, prefix_len).map_err(|_| {
+                DecodeError::Unknown(format!("invalid 6rd prefix length {prefix_len}").into())
+            })?;
+            let br_addrs = decoder.read_ipv4s(decoder.buffer().len())?;
+            SixRd(sixrd::SixRd::new(ipv4_mask_len, prefix, br_addrs))
+        }
         OptionCode::ClientFQDN => {
             debug_assert!(len >= 3);
             let flags = decoder.read_u8()?.into();
@@ -1027,28 +1122,12 @@ fn decode_inner(
             })
         }
         OptionCode::ClasslessStaticRoute => {
-            let mut routes = Vec::new();
-
-            let mut route_dec = Decoder::new(decoder.read_slice(len)?);
-            while let Ok(prefix_len) = route_dec.read_u8() {
-                if prefix_len > 32 {
-                    break;
-                }
-
-                // Significant bytes to hold the prefix
-                let sig_bytes = (prefix_len as usize + 7) / 8;
-
-                let mut dest = [0u8; 4];
-                dest[0..sig_bytes].clone_from_slice(route_dec.read_slice(sig_bytes)?);
-
-                let dest = Ipv4Net::new(dest.into(), prefix_len).unwrap();
-                let gw = route_dec.read_ipv4(4)?;
-
-                routes.push((dest, gw));
-            }
-
-            ClasslessStaticRoute(routes)
+            ClasslessStaticRoute(decode_classless_routes(&mut decoder.sub_decoder(len)?)?)
+        }
+        OptionCode::MsClasslessStaticRoute => {
+            MsClasslessStaticRoute(decode_classless_routes(&mut decoder.sub_decoder(len)?)?)
         }
+        OptionCode::Wpad => Wpad(decoder.read_string(len)?),
         OptionCode::End => End,
         // not yet implemented
         OptionCode::Unknown(code) => {
@@ -1058,12 +1137,56 @@ fn decode_inner(
     })
 }
 
+/// decode RFC 3442 routes. destination bits past the prefix length are kept
+/// as-is, see [`RouteTable`] for resolving them.
+///
+/// [`RouteTable`]: crate::v4::RouteTable
+fn decode_classless_routes(decoder: &mut Decoder<'_>) -> DecodeResult<Vec<(Ipv4Net, Ipv4Addr)>> {
+    let mut routes = Vec::new();
+    while let Ok(prefix_len) = decoder.read_u8() {
+        if prefix_len > 32 {
+            break;
+        }
+
+        // Significant bytes to hold the prefix
+        let sig_bytes = (prefix_len as usize + 7) / 8;
+
+        let mut dest = [0u8; 4];
+        dest[0..sig_bytes].clone_from_slice(decoder.read_slice(sig_bytes)?);
+
+        let dest = Ipv4Net::new(dest.into(), prefix_len).unwrap();
+        let gw = decoder.read_ipv4(4)?;
+
+        routes.push((dest, gw));
+    }
+    Ok(routes)
+}
+
+fn encode_classless_routes(
+    code: OptionCode,
+    routes: &[(Ipv4Net, Ipv4Addr)],
+    e: &mut Encoder<'_>,
+) -> EncodeResult<()> {
+    let mut buf = Vec::new();
+    let mut route_enc = Encoder::new(&mut buf);
+    for (dest, gw) in routes {
+        let byte_len = (dest.prefix_len() + 7) / 8;
+        route_enc.write_u8(dest.prefix_len())?;
+        route_enc.write_slice(&dest.addr().octets()[0..byte_len as usize])?;
+        route_enc.write(gw.octets())?;
+    }
+
+    encode_long_opt_bytes(code, &buf, e)
+}
+
 impl Decodable for DhcpOption {
     #[inline]
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         #[derive(Debug)]
         struct Opt<'a> {
             code: u8,
+            // offset of the (first) option in the original input
+            offset: usize,
             // will contain code + len + value
             buf: Cow<'a, [u8]>,
         }
@@ -1071,18 +1194,23 @@ impl Decodable for DhcpOption {
         impl<'a> Opt<'a> {
             #[inline]
             fn as_option(&self) -> DecodeResult<DhcpOption> {
-                let mut opt_decoder = Decoder::new(&self.buf);
-                let code = opt_decoder.read_u8()?.into();
+                // offsets are exact unless the option was split (RFC 3396),
+                // then they are relative to the start of the first chunk
+                let mut opt_decoder = Decoder::new_at(&self.buf, self.offset);
+                let code: OptionCode = opt_decoder.read_u8()?.into();
                 let _len = opt_decoder.read_u8()?; // throw out potentially invalid len
 
-                decode_inner(code, opt_decoder.buffer().len(), &mut opt_decoder)
+                decode_inner(code, opt_decoder.buffer().len(), &mut opt_decoder).map_err(|err| {
+                    err.with_context(opt_decoder.offset(), PathSegment::Opt(format!("{code:?}")))
+                })
             }
             // can't implement Decodable b/c of lifetime issues
             fn decode(dec: &mut Decoder<'a>) -> DecodeResult<Self> {
                 // TODO: necessary to call u8::from_be_bytes?
                 let [code, len] = dec.peek::<2>()?;
+                let offset = dec.offset();
                 let buf = Cow::from(dec.read_slice(len as usize + 2)?);
-                Ok(Opt { code, buf })
+                Ok(Opt { code, offset, buf })
             }
         }
 
@@ -1134,6 +1262,50 @@ impl Decodable for DhcpOption {
     }
 }
 
+/// Decode the options found in each of `regions`, in order, joining the
+/// chunks of an option by code (RFC 3396). Each region is given with its
+/// offset in the original input, so errors point into that region. Like a
+/// split option in the options field, the offset of an error in a joined
+/// option is relative to its first chunk.
+/// Used when option overload puts options in the `file` & `sname` fields
+pub(crate) fn decode_regions(regions: &[(usize, &[u8])]) -> DecodeResult<DhcpOptions> {
+    // code, offset of the first chunk & the joined value
+    let mut chunks: Vec<(u8, usize, Vec<u8>)> = Vec::new();
+    for &(mut offset, mut rest) in regions {
+        loop {
+            match rest {
+                [0, tail @ ..] => {
+                    rest = tail;
+                    offset += 1;
+                }
+                [] | [255, ..] => break,
+                [code, len, tail @ ..] if tail.len() >= *len as usize => {
+                    let (data, tail) = tail.split_at(*len as usize);
+                    match chunks.iter_mut().find(|(c, ..)| c == code) {
+                        Some((_, _, buf)) => buf.extend_from_slice(data),
+                        None => chunks.push((*code, offset, data.to_vec())),
+                    }
+                    rest = tail;
+                    offset += 2 + data.len();
+                }
+                // truncated option, ignored like in `DhcpOptions::decode`
+                _ => break,
+            }
+        }
+    }
+    let mut opts = DhcpOptions::new();
+    for (code, offset, data) in chunks {
+        let code = OptionCode::from(code);
+        // the value starts after the code & len of the first chunk
+        let mut decoder = Decoder::new_at(&data, offset + 2);
+        let opt = decode_inner(code, data.len(), &mut decoder).map_err(|err| {
+            err.with_context(decoder.offset(), PathSegment::Opt(format!("{code:?}")))
+        })?;
+        opts.insert(opt);
+    }
+    Ok(opts)
+}
+
 /// Splits `bytes` into chunks of up to u8::MAX (255 is the max opt length),
 /// where each chunk is prepended by the length of the chunk and the code.
 /// ```
@@ -1257,6 +1429,7 @@ impl Encodable for DhcpOption {
             | XDisplayManager(ips)
             | NIS(ips)
             | NISServerAddr(ips)
+            | TftpServerAddress(ips)
             | NTPServers(ips)
             | NetBiosNameServers(ips)
             | NetBiosDatagramDistributionServer(ips)
@@ -1270,7 +1443,8 @@ impl Encodable for DhcpOption {
                 // }
             }
             Hostname(s) | MeritDumpFile(s) | DomainName(s) | ExtensionsPath(s) | NISDomain(s)
-            | RootPath(s) | NetBiosScope(s) | Message(s) => {
+            | RootPath(s) | NetBiosScope(s) | Message(s) | Wpad(s) | TftpServerName(s)
+            | BootfileName(s) => {
                 encode_long_opt_bytes(code, s.as_bytes(), e)?;
             }
             BootFileSize(num) | MaxDatagramSize(num) | InterfaceMtu(num) | MaxMessageSize(num) => {
@@ -1382,11 +1556,30 @@ impl Encodable for DhcpOption {
                 e.write_u8(1)?;
                 e.write_u8((*src).into())?
             }
+            SixRd(sixrd) => {
+                let mut buf = vec![sixrd.ipv4_mask_len, sixrd.prefix.prefix_len()];
+                buf.extend(sixrd.prefix.addr().octets());
+                buf.extend(sixrd.br_addrs.iter().flat_map(|ip| ip.octets()));
+                encode_long_opt_bytes(code, &buf, e)?;
+            }
             DomainSearch(names) => {
+                // compress before splitting, pointers are relative to the
+                // concatenated data: https://www.rfc-editor.org/rfc/rfc3397#section-2
+                let buf = Domain::encode_list(names, true)?;
+                encode_long_opt_bytes(code, &buf, e)?;
+            }
+            SipServers(servers) => {
                 let mut buf = Vec::new();
-                let mut name_encoder = BinEncoder::new(&mut buf);
-                for name in names {
-                    name.0.emit(&mut name_encoder)?;
+                match servers {
+                    self::SipServers::Domains(names) => {
+                        buf.push(0);
+                        // compression pointers are relative to the start of the name list
+                        buf.extend(Domain::encode_list(names, true)?);
+                    }
+                    self::SipServers::Addrs(ips) => {
+                        buf.push(1);
+                        buf.extend(ips.iter().flat_map(|ip| ip.octets()));
+                    }
                 }
                 encode_long_opt_bytes(code, &buf, e)?;
             }
@@ -1409,17 +1602,8 @@ impl Encodable for DhcpOption {
                 }
                 encode_long_opt_bytes(code, &buf, e)?;
             }
-            ClasslessStaticRoute(routes) => {
-                let mut buf = Vec::new();
-                let mut route_enc = Encoder::new(&mut buf);
-                for (dest, gw) in routes {
-                    let byte_len = (dest.prefix_len() + 7) / 8;
-                    route_enc.write_u8(dest.prefix_len())?;
-                    route_enc.write_slice(&dest.addr().octets()[0..byte_len as usize])?;
-                    route_enc.write(gw.octets())?;
-                }
-
-                encode_long_opt_bytes(code, &buf, e)?;
+            ClasslessStaticRoute(routes) | MsClasslessStaticRoute(routes) => {
+                encode_classless_routes(code, routes, e)?;
             }
             // not yet implemented
             Unknown(opt) => {
@@ -1493,6 +1677,9 @@ impl From<&DhcpOption> for OptionCode {
             Rebinding(_) => OptionCode::Rebinding,
             ClassIdentifier(_) => OptionCode::ClassIdentifier,
             ClientIdentifier(_) => OptionCode::ClientIdentifier,
+            TftpServerName(_) => OptionCode::TftpServerName,
+            BootfileName(_) => OptionCode::BootfileName,
+            TftpServerAddress(_) => OptionCode::TftpServerAddress,
             RapidCommit => OptionCode::RapidCommit,
             ClientFQDN(_) => OptionCode::ClientFQDN,
             RelayAgentInformation(_) => OptionCode::RelayAgentInformation,
@@ -1504,6 +1691,7 @@ impl From<&DhcpOption> for OptionCode {
             CaptivePortal(_) => OptionCode::CaptivePortal,
             SubnetSelection(_) => OptionCode::SubnetSelection,
             DomainSearch(_) => OptionCode::DomainSearch,
+            SipServers(_) => OptionCode::SipServers,
             BulkLeaseQueryStatusCode(_, _) => OptionCode::StatusCode,
             BulkLeaseQueryBaseTime(_) => OptionCode::BaseTime,
             BulkLeasQueryStartTimeOfState(_) => OptionCode::StartTimeOfState,
@@ -1511,7 +1699,10 @@ impl From<&DhcpOption> for OptionCode {
             BulkLeaseQueryQueryEndTime(_) => OptionCode::QueryEndTime,
             BulkLeaseQueryDhcpState(_) => OptionCode::DhcpState,
             BulkLeaseQueryDataSource(_) => OptionCode::DataSource,
+            SixRd(_) => OptionCode::SixRd,
             ClasslessStaticRoute(_) => OptionCode::ClasslessStaticRoute,
+            MsClasslessStaticRoute(_) => OptionCode::MsClasslessStaticRoute,
+            Wpad(_) => OptionCode::Wpad,
             End => OptionCode::End,
             // TODO: implement more
             Unknown(n) => OptionCode::Unknown(n.code),
@@ -1864,6 +2055,54 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_sip_servers() -> Result<()> {
+        test_opt(
+            DhcpOption::SipServers(SipServers::Domains(vec![
+                Domain(Name::from_str("example.com.").unwrap()),
+                Domain(Name::from_str("sip.example.com.").unwrap()),
+            ])),
+            vec![
+                120, 20, 0, 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3, b'c', b'o', b'm', 0, 3,
+                b's', b'i', b'p', 0xC0, 0x00,
+            ],
+        )?;
+        test_opt(
+            DhcpOption::SipServers(SipServers::Addrs(vec![
+                "192.168.0.1".parse()?,
+                "192.168.0.2".parse()?,
+            ])),
+            vec![120, 9, 1, 192, 168, 0, 1, 192, 168, 0, 2],
+        )?;
+        // unknown encoding
+        test_opt(
+            DhcpOption::Unknown(UnknownOption::new(OptionCode::SipServers, vec![2, 1])),
+            vec![120, 2, 2, 1],
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_domainsearch_long_opt() -> Result<()> {
+        // long enough to be split, later names point back into the first chunk
+        let names = (0..40)
+            .map(|i| Domain(Name::from_str(&format!("host{i}.example.com.")).unwrap()))
+            .collect::<Vec<_>>();
+        let opt = DhcpOption::DomainSearch(names.clone());
+        let mut buf = Vec::new();
+        opt.encode(&mut Encoder::new(&mut buf))?;
+        assert_eq!(buf[0], 119);
+        assert_eq!(buf[1], 255);
+        // first name written in full, every other name is `hostN` + pointer
+        let uncompressed = names.iter().map(|n| n.0.len() + 1).sum::<usize>();
+        assert!(buf.len() < uncompressed);
+
+        let decoded = DhcpOption::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(decoded, opt);
+        Ok(())
+    }
+
     #[test]
     fn test_client_fqdn() -> Result<()> {
         test_opt(
@@ -1950,6 +2189,78 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_ms_classless_static_route() -> Result<()> {
+        test_opt(
+            DhcpOption::MsClasslessStaticRoute(vec![
+                ("0.0.0.0/0".parse()?, "192.168.1.1".parse()?),
+                ("10.0.0.0/8".parse()?, "192.168.1.1".parse()?),
+            ]),
+            vec![
+                249, 11, // Option & length
+                0, 192, 168, 1, 1, // default -> 192.168.1.1
+                8, 10, 192, 168, 1, 1, // 10.0.0.0/8 -> 192.168.1.1
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wpad() -> Result<()> {
+        test_opt(
+            DhcpOption::Wpad("http://wpad/wpad.dat\0".to_string()),
+            vec![
+                252, 21, b'h', b't', b't', b'p', b':', b'/', b'/', b'w', b'p', b'a', b'd', b'/',
+                b'w', b'p', b'a', b'd', b'.', b'd', b'a', b't', 0,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sixrd() -> Result<()> {
+        let mut bytes = vec![212, 26, 8, 32, 0x20, 0x01, 0x0d, 0xb8];
+        bytes.extend([0; 12]);
+        bytes.extend([10, 0, 0, 1, 10, 0, 0, 2]);
+        test_opt(
+            DhcpOption::SixRd(sixrd::SixRd::new(
+                8,
+                "2001:db8::/32".parse()?,
+                vec!["10.0.0.1".parse()?, "10.0.0.2".parse()?],
+            )),
+            bytes,
+        )?;
+
+        // bad prefix length & truncated
+        let mut bytes = vec![212, 22, 0, 129];
+        bytes.extend([0; 20]);
+        assert!(DhcpOption::decode(&mut Decoder::new(&bytes)).is_err());
+        assert!(DhcpOption::decode(&mut Decoder::new(&[212, 2, 0, 32])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tftp() -> Result<()> {
+        test_opt(
+            DhcpOption::TftpServerName("tftp".to_string()),
+            vec![66, 4, b't', b'f', b't', b'p'],
+        )?;
+        test_opt(
+            DhcpOption::BootfileName("pxelinux.0".to_string()),
+            vec![
+                67, 10, b'p', b'x', b'e', b'l', b'i', b'n', b'u', b'x', b'.', b'0',
+            ],
+        )?;
+        test_opt(
+            DhcpOption::TftpServerAddress(vec!["10.0.0.1".parse()?, "10.0.0.2".parse()?]),
+            vec![150, 8, 10, 0, 0, 1, 10, 0, 0, 2],
+        )?;
+
+        Ok(())
+    }
+
     fn binput() -> (Vec<u8>, usize) {
         (
             vec![
@@ -107,6 +107,8 @@ pub enum RelayInfo {
     RelayAgentFlags(RelayFlags),
     /// 11 - <https://datatracker.ietf.org/doc/html/rfc5107#section-4>
     ServerIdentifierOverride(Ipv4Addr),
+    /// 12 - <https://datatracker.ietf.org/doc/html/rfc6925#section-4>
+    RelayId(Vec<u8>),
     Unknown(UnknownInfo),
     // TODO: not tackling this at the moment
     // 7 - <https://datatracker.ietf.org/doc/html/rfc4014>
@@ -134,7 +136,7 @@ impl Decodable for RelayInfo {
             RelayCode::AgentRemoteId => {
                 let len = d.read_u8()? as usize;
                 let data = d.read_slice(len)?.to_vec();
-                AgentCircuitId(data)
+                AgentRemoteId(data)
             }
             RelayCode::DocsisDeviceClass => {
                 let _ = d.read_u8()?;
@@ -159,6 +161,10 @@ impl Decodable for RelayInfo {
                 let len = d.read_u8()? as usize;
                 ServerIdentifierOverride(d.read_ipv4(len)?)
             }
+            RelayCode::RelayId => {
+                let len = d.read_u8()? as usize;
+                RelayId(d.read_slice(len)?.to_vec())
+            }
             // we have codes for these but not full type definitions yet
             code @ (RelayCode::Authentication
             | RelayCode::VirtualSubnet
@@ -188,7 +194,7 @@ impl Encodable for RelayInfo {
         let code: RelayCode = self.into();
         e.write_u8(code.into())?;
         match self {
-            AgentCircuitId(id) | AgentRemoteId(id) | SubscriberId(id) => {
+            AgentCircuitId(id) | AgentRemoteId(id) | SubscriberId(id) | RelayId(id) => {
                 // length of bytes stored in Vec
                 e.write_u8(id.len() as u8)?;
                 e.write_slice(id)?
@@ -304,6 +310,7 @@ pub enum RelayCode {
     VendorSpecificInformation,
     RelayAgentFlags,
     ServerIdentifierOverride,
+    RelayId,
     VirtualSubnet,
     VirtualSubnetControl,
     /// unknown/unimplemented message type
@@ -324,6 +331,7 @@ impl From<u8> for RelayCode {
             9 => VendorSpecificInformation,
             10 => RelayAgentFlags,
             11 => ServerIdentifierOverride,
+            12 => RelayId,
             151 => VirtualSubnet,
             152 => VirtualSubnetControl,
             _ => Unknown(n),
@@ -344,6 +352,7 @@ impl From<RelayCode> for u8 {
             VendorSpecificInformation => 9,
             RelayAgentFlags => 10,
             ServerIdentifierOverride => 11,
+            RelayId => 12,
             VirtualSubnet => 151,
             VirtualSubnetControl => 152,
             Unknown(n) => n,
@@ -362,6 +371,7 @@ impl From<&RelayInfo> for RelayCode {
             SubscriberId(_) => RelayCode::SubscriberId,
             RelayAgentFlags(_) => RelayCode::RelayAgentFlags,
             ServerIdentifierOverride(_) => RelayCode::ServerIdentifierOverride,
+            RelayId(_) => RelayCode::RelayId,
             Unknown(unknown) => RelayCode::Unknown(unknown.code),
         }
     }
@@ -411,6 +421,8 @@ mod tests {
             RelayInfo::AgentCircuitId(vec![0, 1, 2, 3, 4]),
             vec![1, 5, 0, 1, 2, 3, 4],
         )?;
+        test_opt(RelayInfo::AgentRemoteId(vec![0, 1, 2]), vec![2, 3, 0, 1, 2])?;
+        test_opt(RelayInfo::RelayId(vec![0, 1, 2]), vec![12, 3, 0, 1, 2])?;
 
         Ok(())
     }
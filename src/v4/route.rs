@@ -0,0 +1,285 @@
+use std::net::Ipv4Addr;
+
+use ipnet::Ipv4Net;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::v4::{DhcpOption, DhcpOptions, OptionCode};
+
+/// A single route. A gateway of `0.0.0.0` means the destination is on-link
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Route {
+    /// destination network
+    pub dest: Ipv4Net,
+    /// next hop
+    pub gateway: Ipv4Addr,
+}
+
+impl Route {
+    /// create a new route
+    pub fn new(dest: Ipv4Net, gateway: Ipv4Addr) -> Self {
+        Self { dest, gateway }
+    }
+    /// true if the destination has no bits set past its prefix length
+    pub fn is_canonical(&self) -> bool {
+        self.dest.addr() == self.dest.network()
+    }
+}
+
+/// Errors from building a [`RouteTable`]
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    /// destination has bits set past its prefix length
+    #[error("route destination {0} has bits set past the prefix length")]
+    NonCanonical(Ipv4Net),
+}
+
+/// The routes a client should install, resolved from the Router (3),
+/// Static Route (33), Classless Static Route (121) & Microsoft Classless
+/// Static Route (249) options following [RFC 3442]:
+///
+/// - if 121 is present, 3 & 33 are ignored
+/// - otherwise, if 249 is present it is used like 121
+/// - otherwise, routes from 33 are used, plus a default route via the first router in 3
+///
+/// ```rust
+/// # use dhcproto::v4::{DhcpOption, DhcpOptions, RouteTable};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut opts = DhcpOptions::new();
+/// opts.insert(DhcpOption::Router(vec!["192.168.0.1".parse()?]));
+/// opts.insert(DhcpOption::ClasslessStaticRoute(vec![
+///     ("10.0.0.0/8".parse()?, "192.168.0.254".parse()?),
+/// ]));
+/// let table = RouteTable::from_opts(&opts);
+/// // the Router option is ignored when 121 is present
+/// assert_eq!(table.default_gateway(), None);
+/// assert_eq!(table.lookup("10.1.2.3".parse()?).unwrap().gateway, "192.168.0.254".parse::<std::net::Ipv4Addr>()?);
+/// # Ok(()) }
+/// ```
+///
+/// [RFC 3442]: https://www.rfc-editor.org/rfc/rfc3442
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RouteTable {
+    routes: Vec<Route>,
+}
+
+impl RouteTable {
+    /// create an empty route table
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// resolve the effective routes from `opts`. Destination bits past the
+    /// prefix length are zeroed, as clients are required to do by RFC 3442
+    pub fn from_opts(opts: &DhcpOptions) -> Self {
+        let mut table = Self::resolve(opts);
+        for route in &mut table.routes {
+            route.dest = route.dest.trunc();
+        }
+        table
+    }
+    /// like [`RouteTable::from_opts`], but returns an error if any
+    /// classless destination has bits set past its prefix length
+    pub fn try_from_opts(opts: &DhcpOptions) -> Result<Self, RouteError> {
+        let table = Self::resolve(opts);
+        if let Some(route) = table.routes.iter().find(|r| !r.is_canonical()) {
+            return Err(RouteError::NonCanonical(route.dest));
+        }
+        Ok(table)
+    }
+    fn resolve(opts: &DhcpOptions) -> Self {
+        let classless = match opts
+            .get(OptionCode::ClasslessStaticRoute)
+            .or_else(|| opts.get(OptionCode::MsClasslessStaticRoute))
+        {
+            Some(DhcpOption::ClasslessStaticRoute(routes))
+            | Some(DhcpOption::MsClasslessStaticRoute(routes)) => Some(routes),
+            _ => None,
+        };
+        if let Some(routes) = classless {
+            return routes
+                .iter()
+                .map(|(dest, gw)| Route::new(*dest, *gw))
+                .collect();
+        }
+
+        let mut table = RouteTable::new();
+        if let Some(DhcpOption::StaticRoutingTable(routes)) =
+            opts.get(OptionCode::StaticRoutingTable)
+        {
+            for (dest, gw) in routes {
+                // 0.0.0.0 is not a valid destination for option 33
+                if !dest.is_unspecified() {
+                    table.push(Route::new(classful(*dest), *gw));
+                }
+            }
+        }
+        if let Some(DhcpOption::Router(routers)) = opts.get(OptionCode::Router) {
+            if let Some(router) = routers.first() {
+                table.push(Route::new(Ipv4Net::default(), *router));
+            }
+        }
+        table
+    }
+    /// add a route
+    pub fn push(&mut self, route: Route) -> &mut Self {
+        self.routes.push(route);
+        self
+    }
+    /// the routes in this table
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+    /// the gateway of the default route (`0.0.0.0/0`), if any
+    pub fn default_gateway(&self) -> Option<Ipv4Addr> {
+        self.routes
+            .iter()
+            .find(|r| r.dest.prefix_len() == 0)
+            .map(|r| r.gateway)
+    }
+    /// find the route for `addr` using longest prefix match
+    pub fn lookup(&self, addr: Ipv4Addr) -> Option<&Route> {
+        self.routes
+            .iter()
+            .filter(|r| r.dest.trunc().contains(&addr))
+            // max_by_key returns the last max element, reverse so the first route wins ties
+            .rev()
+            .max_by_key(|r| r.dest.prefix_len())
+    }
+    /// encode as a Classless Static Route (121) option. Returns an error if
+    /// any destination has bits set past its prefix length
+    pub fn to_classless_opt(&self) -> Result<DhcpOption, RouteError> {
+        Ok(DhcpOption::ClasslessStaticRoute(self.to_pairs()?))
+    }
+    /// encode as a Microsoft Classless Static Route (249) option. Returns an
+    /// error if any destination has bits set past its prefix length
+    pub fn to_ms_classless_opt(&self) -> Result<DhcpOption, RouteError> {
+        Ok(DhcpOption::MsClasslessStaticRoute(self.to_pairs()?))
+    }
+    fn to_pairs(&self) -> Result<Vec<(Ipv4Net, Ipv4Addr)>, RouteError> {
+        self.routes
+            .iter()
+            .map(|r| {
+                if r.is_canonical() {
+                    Ok((r.dest, r.gateway))
+                } else {
+                    Err(RouteError::NonCanonical(r.dest))
+                }
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<Route> for RouteTable {
+    fn from_iter<T: IntoIterator<Item = Route>>(iter: T) -> Self {
+        Self {
+            routes: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// option 33 destinations are classful, use the natural mask unless the
+/// address has bits set past it, then it's a host route
+fn classful(dest: Ipv4Addr) -> Ipv4Net {
+    let prefix_len = match dest.octets()[0] {
+        0..=127 => 8,
+        128..=191 => 16,
+        192..=223 => 24,
+        _ => 32,
+    };
+    let net = Ipv4Net::new(dest, prefix_len).unwrap();
+    if net.network() == dest {
+        net
+    } else {
+        Ipv4Net::new(dest, 32).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    #[test]
+    fn test_route_resolution() -> Result<()> {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::Router(vec![
+            "192.168.0.1".parse()?,
+            "192.168.0.2".parse()?,
+        ]));
+        opts.insert(DhcpOption::StaticRoutingTable(vec![
+            ("10.0.0.0".parse()?, "192.168.0.3".parse()?),
+            ("172.16.5.4".parse()?, "192.168.0.4".parse()?),
+        ]));
+        let table = RouteTable::from_opts(&opts);
+        assert_eq!(
+            table.routes(),
+            &[
+                Route::new("10.0.0.0/8".parse()?, "192.168.0.3".parse()?),
+                Route::new("172.16.5.4/32".parse()?, "192.168.0.4".parse()?),
+                Route::new("0.0.0.0/0".parse()?, "192.168.0.1".parse()?),
+            ]
+        );
+        assert_eq!(table.default_gateway(), Some("192.168.0.1".parse()?));
+        assert_eq!(
+            table.lookup("10.1.1.1".parse()?).unwrap().gateway,
+            "192.168.0.3".parse::<Ipv4Addr>()?
+        );
+        assert_eq!(
+            table.lookup("8.8.8.8".parse()?).unwrap().gateway,
+            "192.168.0.1".parse::<Ipv4Addr>()?
+        );
+
+        // 249 overrides 3 & 33
+        opts.insert(DhcpOption::MsClasslessStaticRoute(vec![(
+            "10.0.0.0/8".parse()?,
+            "192.168.0.5".parse()?,
+        )]));
+        let table = RouteTable::from_opts(&opts);
+        assert_eq!(
+            table.routes(),
+            &[Route::new("10.0.0.0/8".parse()?, "192.168.0.5".parse()?)]
+        );
+        assert_eq!(table.lookup("8.8.8.8".parse()?), None);
+
+        // 121 overrides 249
+        opts.insert(DhcpOption::ClasslessStaticRoute(vec![
+            ("10.0.0.0/8".parse()?, "192.168.0.6".parse()?),
+            ("10.1.0.0/16".parse()?, "192.168.0.7".parse()?),
+        ]));
+        let table = RouteTable::from_opts(&opts);
+        assert_eq!(
+            table.lookup("10.1.1.1".parse()?).unwrap().gateway,
+            "192.168.0.7".parse::<Ipv4Addr>()?
+        );
+        assert_eq!(
+            table.to_classless_opt()?,
+            opts.get(OptionCode::ClasslessStaticRoute).unwrap().clone()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_route_non_canonical() -> Result<()> {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::ClasslessStaticRoute(vec![(
+            "10.1.0.0/8".parse()?,
+            "192.168.0.1".parse()?,
+        )]));
+        assert_eq!(
+            RouteTable::try_from_opts(&opts),
+            Err(RouteError::NonCanonical("10.1.0.0/8".parse()?))
+        );
+        let table = RouteTable::from_opts(&opts);
+        assert_eq!(table.routes()[0].dest, "10.0.0.0/8".parse()?);
+
+        let table: RouteTable = [Route::new("10.1.0.0/8".parse()?, Ipv4Addr::UNSPECIFIED)]
+            .into_iter()
+            .collect();
+        assert!(table.to_ms_classless_opt().is_err());
+        Ok(())
+    }
+}
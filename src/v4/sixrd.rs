@@ -0,0 +1,104 @@
+//! IPv6 Rapid Deployment on IPv4 infrastructure, 6rd ([RFC 5969])
+//!
+//! The 6rd option tells a CE router how to build its delegated IPv6 prefix
+//! from its own IPv4 address, and which border relays to tunnel to.
+//!
+//! ```rust
+//! use dhcproto::v4::sixrd::SixRd;
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // the common bits 10/8 are left out of the 6rd prefix
+//! let sixrd = SixRd::new(8, "2001:db8::/32".parse()?, vec!["10.0.0.1".parse()?]);
+//! assert_eq!(
+//!     sixrd.delegated_prefix("10.100.200.1".parse()?),
+//!     Some("2001:db8:64c8:100::/56".parse()?)
+//! );
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 5969]: https://datatracker.ietf.org/doc/html/rfc5969
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use ipnet::Ipv6Net;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// 6rd option (212) - <https://datatracker.ietf.org/doc/html/rfc5969#section-7.1.1>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SixRd {
+    /// number of high-order bits common to every CE's IPv4 address, which
+    /// are not carried in the delegated prefix
+    pub ipv4_mask_len: u8,
+    /// the 6rd prefix & its length
+    pub prefix: Ipv6Net,
+    /// IPv4 addresses of the 6rd border relays
+    pub br_addrs: Vec<Ipv4Addr>,
+}
+
+impl SixRd {
+    /// create a new 6rd option
+    pub fn new(ipv4_mask_len: u8, prefix: Ipv6Net, br_addrs: Vec<Ipv4Addr>) -> Self {
+        Self {
+            ipv4_mask_len,
+            prefix,
+            br_addrs,
+        }
+    }
+    /// the prefix delegated to a CE with address `addr`: the 6rd prefix
+    /// followed by the bits of `addr` after the first `ipv4_mask_len`.
+    /// Returns `None` if the mask length is over 32 or the delegated prefix
+    /// would be longer than 128 bits
+    pub fn delegated_prefix(&self, addr: Ipv4Addr) -> Option<Ipv6Net> {
+        if self.ipv4_mask_len > 32 {
+            return None;
+        }
+        let suffix_len = 32 - self.ipv4_mask_len;
+        let len = self.prefix.prefix_len() + suffix_len;
+        if len > 128 {
+            return None;
+        }
+        let suffix = u32::from(addr) as u128 & ((1u128 << suffix_len) - 1);
+        let prefix =
+            u128::from(self.prefix.network()) | suffix.checked_shl(128 - len as u32).unwrap_or(0);
+        Ipv6Net::new(Ipv6Addr::from(prefix), len).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    #[test]
+    fn test_delegated_prefix() -> Result<()> {
+        // RFC 5969 section 4, no common bits
+        let sixrd = SixRd::new(0, "2001:db8::/32".parse()?, vec![]);
+        assert_eq!(
+            sixrd.delegated_prefix("192.0.2.1".parse()?),
+            Some("2001:db8:c000:201::/64".parse()?)
+        );
+        // whole address is common, the 6rd prefix is delegated as-is
+        let sixrd = SixRd::new(32, "2001:db8:1::/48".parse()?, vec![]);
+        assert_eq!(
+            sixrd.delegated_prefix("192.0.2.1".parse()?),
+            Some("2001:db8:1::/48".parse()?)
+        );
+        // host bits in the 6rd prefix are ignored
+        let sixrd = SixRd::new(16, "2001:db8:ffff::/32".parse()?, vec![]);
+        assert_eq!(
+            sixrd.delegated_prefix("192.168.10.20".parse()?),
+            Some("2001:db8:a14::/48".parse()?)
+        );
+
+        assert_eq!(
+            SixRd::new(0, "2001:db8::/100".parse()?, vec![]).delegated_prefix(Ipv4Addr::LOCALHOST),
+            None
+        );
+        assert_eq!(
+            SixRd::new(33, "2001:db8::/32".parse()?, vec![]).delegated_prefix(Ipv4Addr::LOCALHOST),
+            None
+        );
+        Ok(())
+    }
+}
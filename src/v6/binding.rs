@@ -0,0 +1,448 @@
+//! Typed IA bindings from a `REPLY` ([RFC 8415])
+//!
+//! [`Message::bindings`] reads each IA_NA, IA_TA & IA_PD into an
+//! [`IaBinding`], [`Message::lease_summary`] checks them against the
+//! RFC 8415 rules and sorts the leases into those to use & those released.
+//!
+//! ```rust
+//! use dhcproto::v6::{DhcpOption, IAAddr, Message, MessageType, IANA};
+//! use std::time::Duration;
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut reply = Message::new(MessageType::Reply);
+//! let mut iana = IANA { id: 1, t1: 1800, t2: 2880, opts: Default::default() };
+//! iana.opts.insert(DhcpOption::IAAddr(IAAddr {
+//!     addr: "2001:db8::10".parse()?,
+//!     preferred_life: 3600,
+//!     valid_life: 7200,
+//!     opts: Default::default(),
+//! }));
+//! reply.opts_mut().insert(DhcpOption::IANA(iana));
+//!
+//! let summary = reply.lease_summary();
+//! assert_eq!(summary.active.len(), 1);
+//! assert_eq!(summary.active[0].valid, Duration::from_secs(7200));
+//! assert_eq!(summary.renew, Some(Duration::from_secs(1800)));
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 8415]: https://datatracker.ietf.org/doc/html/rfc8415
+//! [`Message::bindings`]: crate::v6::Message::bindings
+//! [`Message::lease_summary`]: crate::v6::Message::lease_summary
+use std::{net::Ipv6Addr, time::Duration};
+
+use ipnet::Ipv6Net;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::v6::{DhcpOption, DhcpOptions, OptionCode, Status, StatusCode};
+
+/// a lifetime of 0xffffffff, which never expires
+pub const INFINITY: Duration = Duration::from_secs(u32::MAX as u64);
+
+/// Kind of identity association
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IaKind {
+    /// IA_NA (3)
+    NonTemporary,
+    /// IA_TA (4)
+    Temporary,
+    /// IA_PD (25)
+    PrefixDelegation,
+}
+
+/// What a lease is for
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Leased {
+    /// an address from an IA Address option (5)
+    Addr(Ipv6Addr),
+    /// a delegated prefix from an IA Prefix option (26)
+    Prefix(Ipv6Net),
+}
+
+/// An address or prefix leased in an IA
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IaLease {
+    /// IAID of the IA holding the lease
+    pub iaid: u32,
+    /// the leased address or prefix
+    pub leased: Leased,
+    /// preferred lifetime
+    pub preferred: Duration,
+    /// valid lifetime, 0 if the server released the lease
+    pub valid: Duration,
+    /// status code sent with the lease, if any
+    pub status: Option<StatusCode>,
+}
+
+impl IaLease {
+    /// true if the valid lifetime is 0, the lease must no longer be used
+    pub fn is_released(&self) -> bool {
+        self.valid.is_zero()
+    }
+    /// true if the lifetime never expires
+    pub fn is_infinite(&self) -> bool {
+        self.valid == INFINITY
+    }
+    /// check the lease follows RFC 8415, the preferred lifetime must not
+    /// be greater than the valid lifetime
+    pub fn validate(&self) -> Result<(), BindingError> {
+        if self.preferred > self.valid {
+            return Err(BindingError::PreferredGreaterThanValid {
+                iaid: self.iaid,
+                leased: self.leased,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// An IA_NA, IA_TA or IA_PD from a message
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IaBinding {
+    /// kind of IA
+    pub kind: IaKind,
+    /// IAID
+    pub iaid: u32,
+    /// time until the client renews, 0 for IA_TA or to let the client choose
+    pub t1: Duration,
+    /// time until the client rebinds, 0 for IA_TA or to let the client choose
+    pub t2: Duration,
+    /// status code of the IA, if any
+    pub status: Option<StatusCode>,
+    /// addresses or prefixes in the IA
+    pub leases: Vec<IaLease>,
+}
+
+impl IaBinding {
+    /// read an IA option, `None` if `opt` isn't one
+    pub fn from_option(opt: &DhcpOption) -> Option<Self> {
+        let (kind, iaid, t1, t2, opts) = match opt {
+            DhcpOption::IANA(ia) => (IaKind::NonTemporary, ia.id, ia.t1, ia.t2, &ia.opts),
+            DhcpOption::IATA(ia) => (IaKind::Temporary, ia.id, 0, 0, &ia.opts),
+            DhcpOption::IAPD(ia) => (IaKind::PrefixDelegation, ia.id, ia.t1, ia.t2, &ia.opts),
+            _ => return None,
+        };
+        let leases = opts
+            .iter()
+            .filter_map(|opt| match opt {
+                DhcpOption::IAAddr(addr) => Some(IaLease {
+                    iaid,
+                    leased: Leased::Addr(addr.addr),
+                    preferred: secs(addr.preferred_life),
+                    valid: secs(addr.valid_life),
+                    status: status(&addr.opts),
+                }),
+                DhcpOption::IAPrefix(prefix) => Some(IaLease {
+                    iaid,
+                    leased: Leased::Prefix(prefix.prefix()?),
+                    preferred: secs(prefix.preferred_lifetime),
+                    valid: secs(prefix.valid_lifetime),
+                    status: status(&prefix.opts),
+                }),
+                _ => None,
+            })
+            .collect();
+        Some(Self {
+            kind,
+            iaid,
+            t1: secs(t1),
+            t2: secs(t2),
+            status: status(opts),
+            leases,
+        })
+    }
+    /// true unless the IA has a status code other than `Success`
+    pub fn is_success(&self) -> bool {
+        !matches!(&self.status, Some(code) if code.status != Status::Success)
+    }
+    /// check the IA follows RFC 8415, T1 must not be greater than a
+    /// non-zero T2
+    pub fn validate(&self) -> Result<(), BindingError> {
+        if !self.t2.is_zero() && self.t1 > self.t2 {
+            return Err(BindingError::T1GreaterThanT2 {
+                iaid: self.iaid,
+                t1: self.t1,
+                t2: self.t2,
+            });
+        }
+        Ok(())
+    }
+    /// time until the client should renew. When the server leaves T1 to the
+    /// client this is half the shortest preferred lifetime, as RFC 8415
+    /// recommends. `None` if there is nothing to renew
+    pub fn renew_after(&self) -> Option<Duration> {
+        if !self.t1.is_zero() {
+            return Some(self.t1);
+        }
+        self.shortest_preferred().map(|d| d / 2)
+    }
+    /// time until the client should rebind. When the server leaves T2 to
+    /// the client this is 0.8 times the shortest preferred lifetime
+    pub fn rebind_after(&self) -> Option<Duration> {
+        if !self.t2.is_zero() {
+            return Some(self.t2);
+        }
+        self.shortest_preferred().map(|d| d * 4 / 5)
+    }
+
+    fn shortest_preferred(&self) -> Option<Duration> {
+        self.leases
+            .iter()
+            .filter(|lease| !lease.is_released())
+            .map(|lease| lease.preferred)
+            .min()
+    }
+}
+
+/// RFC 8415 rules an IA or lease breaks. The client must discard it
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingError {
+    /// T1 is greater than T2
+    #[error("IA {iaid:#x} T1 {t1:?} is greater than T2 {t2:?}")]
+    T1GreaterThanT2 {
+        /// IAID
+        iaid: u32,
+        /// T1
+        t1: Duration,
+        /// T2
+        t2: Duration,
+    },
+    /// a preferred lifetime is greater than the valid lifetime
+    #[error("IA {iaid:#x} lease {leased:?} preferred lifetime is greater than valid lifetime")]
+    PreferredGreaterThanValid {
+        /// IAID
+        iaid: u32,
+        /// the lease
+        leased: Leased,
+    },
+}
+
+/// The outcome of a `REPLY`, see [`Message::lease_summary`]
+///
+/// [`Message::lease_summary`]: crate::v6::Message::lease_summary
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LeaseSummary {
+    /// status code of the message, if any. When it isn't `Success` no IA
+    /// was processed
+    pub status: Option<StatusCode>,
+    /// leases to use
+    pub active: Vec<IaLease>,
+    /// leases whose valid lifetime is 0, stop using them
+    pub released: Vec<IaLease>,
+    /// IAs the server couldn't satisfy, with their status
+    pub failed: Vec<(IaKind, u32, StatusCode)>,
+    /// IAs or leases discarded for breaking RFC 8415
+    pub invalid: Vec<BindingError>,
+    /// shortest time until an IA with active leases should be renewed
+    pub renew: Option<Duration>,
+    /// shortest time until an IA with active leases should be rebound
+    pub rebind: Option<Duration>,
+}
+
+impl LeaseSummary {
+    /// summarize `bindings`, the status of the message they came from is
+    /// `status`
+    pub fn new(status: Option<StatusCode>, bindings: &[IaBinding]) -> Self {
+        let mut summary = LeaseSummary {
+            status,
+            ..Default::default()
+        };
+        if matches!(&summary.status, Some(code) if code.status != Status::Success) {
+            return summary;
+        }
+        for binding in bindings {
+            if let Err(err) = binding.validate() {
+                summary.invalid.push(err);
+                continue;
+            }
+            match &binding.status {
+                Some(code) if code.status != Status::Success => {
+                    summary
+                        .failed
+                        .push((binding.kind, binding.iaid, code.clone()));
+                    continue;
+                }
+                _ => {}
+            }
+            let mut any_active = false;
+            for lease in &binding.leases {
+                if let Err(err) = lease.validate() {
+                    summary.invalid.push(err);
+                } else if lease.is_released() {
+                    summary.released.push(lease.clone());
+                } else {
+                    any_active = true;
+                    summary.active.push(lease.clone());
+                }
+            }
+            if any_active {
+                summary.renew = min(summary.renew, binding.renew_after());
+                summary.rebind = min(summary.rebind, binding.rebind_after());
+            }
+        }
+        summary
+    }
+}
+
+fn min(a: Option<Duration>, b: Option<Duration>) -> Option<Duration> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn secs(n: u32) -> Duration {
+    Duration::from_secs(n as u64)
+}
+
+pub(crate) fn status(opts: &DhcpOptions) -> Option<StatusCode> {
+    match opts.get(OptionCode::StatusCode)? {
+        DhcpOption::StatusCode(code) => Some(code.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v6::{IAAddr, IAPrefix, Message, MessageType, IANA, IAPD, IATA};
+
+    fn addr(addr: &str, preferred_life: u32, valid_life: u32) -> DhcpOption {
+        DhcpOption::IAAddr(IAAddr {
+            addr: addr.parse().unwrap(),
+            preferred_life,
+            valid_life,
+            opts: DhcpOptions::new(),
+        })
+    }
+
+    fn iana(id: u32, t1: u32, t2: u32, opts: Vec<DhcpOption>) -> DhcpOption {
+        let mut ia = IANA {
+            id,
+            t1,
+            t2,
+            opts: DhcpOptions::new(),
+        };
+        for opt in opts {
+            ia.opts.insert(opt);
+        }
+        DhcpOption::IANA(ia)
+    }
+
+    fn code(status: Status) -> DhcpOption {
+        DhcpOption::StatusCode(StatusCode {
+            status,
+            msg: String::new(),
+        })
+    }
+
+    #[test]
+    fn test_bindings() {
+        let mut msg = Message::new(MessageType::Reply);
+        msg.opts_mut()
+            .insert(iana(1, 100, 160, vec![addr("2001:db8::1", 300, 600)]));
+        let mut pd = IAPD {
+            id: 2,
+            t1: 0,
+            t2: 0,
+            opts: DhcpOptions::new(),
+        };
+        pd.opts.insert(DhcpOption::IAPrefix(IAPrefix::new(
+            "2001:db8:1::/56".parse().unwrap(),
+            1000,
+            u32::MAX,
+        )));
+        msg.opts_mut().insert(DhcpOption::IAPD(pd));
+        let mut ta = IATA {
+            id: 3,
+            opts: DhcpOptions::new(),
+        };
+        ta.opts.insert(addr("2001:db8::2", 50, 100));
+        msg.opts_mut().insert(DhcpOption::IATA(ta));
+
+        let bindings = msg.bindings();
+        assert_eq!(bindings.len(), 3);
+        assert_eq!(bindings[0].kind, IaKind::NonTemporary);
+        assert_eq!(bindings[0].t1, Duration::from_secs(100));
+        assert_eq!(bindings[1].kind, IaKind::Temporary);
+        assert_eq!(bindings[1].renew_after(), Some(Duration::from_secs(25)));
+        assert_eq!(bindings[2].kind, IaKind::PrefixDelegation);
+        assert_eq!(
+            bindings[2].leases[0].leased,
+            Leased::Prefix("2001:db8:1::/56".parse().unwrap())
+        );
+        assert!(bindings[2].leases[0].is_infinite());
+        // 0.5 & 0.8 of the preferred lifetime when T1/T2 are 0
+        assert_eq!(bindings[2].renew_after(), Some(Duration::from_secs(500)));
+        assert_eq!(bindings[2].rebind_after(), Some(Duration::from_secs(800)));
+
+        let summary = msg.lease_summary();
+        assert_eq!(summary.active.len(), 3);
+        assert!(summary.released.is_empty() && summary.invalid.is_empty());
+        assert_eq!(summary.renew, Some(Duration::from_secs(25)));
+        assert_eq!(summary.rebind, Some(Duration::from_secs(40)));
+    }
+
+    #[test]
+    fn test_lease_summary_rules() {
+        let mut msg = Message::new(MessageType::Reply);
+        // T1 > T2, discarded
+        msg.opts_mut()
+            .insert(iana(1, 200, 100, vec![addr("2001:db8::1", 300, 600)]));
+        // released, preferred > valid & a good lease
+        msg.opts_mut().insert(iana(
+            2,
+            100,
+            200,
+            vec![
+                addr("2001:db8::2", 0, 0),
+                addr("2001:db8::3", 700, 600),
+                addr("2001:db8::4", 300, 600),
+            ],
+        ));
+        // no addresses
+        msg.opts_mut()
+            .insert(iana(3, 0, 0, vec![code(Status::NoAddrsAvail)]));
+        // only released leases don't affect renewal
+        msg.opts_mut()
+            .insert(iana(4, 10, 20, vec![addr("2001:db8::5", 0, 0)]));
+
+        let summary = msg.lease_summary();
+        assert_eq!(
+            summary.invalid,
+            vec![
+                BindingError::T1GreaterThanT2 {
+                    iaid: 1,
+                    t1: Duration::from_secs(200),
+                    t2: Duration::from_secs(100),
+                },
+                BindingError::PreferredGreaterThanValid {
+                    iaid: 2,
+                    leased: Leased::Addr("2001:db8::3".parse().unwrap()),
+                },
+            ]
+        );
+        assert_eq!(summary.active.len(), 1);
+        assert_eq!(
+            summary.active[0].leased,
+            Leased::Addr("2001:db8::4".parse().unwrap())
+        );
+        assert_eq!(summary.released.len(), 2);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].2.status, Status::NoAddrsAvail);
+        assert_eq!(summary.renew, Some(Duration::from_secs(100)));
+
+        // a failed message processes no IAs
+        msg.opts_mut().insert(code(Status::UnspecFail));
+        let summary = msg.lease_summary();
+        assert!(summary.active.is_empty() && summary.failed.is_empty());
+        assert_eq!(summary.status.unwrap().status, Status::UnspecFail);
+    }
+}
@@ -0,0 +1,196 @@
+//! DHCPv4 over DHCPv6 ([RFC 7341])
+//!
+//! On an IPv6-only network a 4o6 client sends its DHCPv4 messages inside a
+//! `DHCPV4-QUERY`, and the server answers inside a `DHCPV4-RESPONSE`. Each
+//! carries the whole DHCPv4 message in a [`DhcpOption::Dhcpv4Msg`]. The 3
+//! bytes holding the transaction id in other DHCPv6 messages are flags
+//! instead, see [`Dhcp4o6Flags`].
+//!
+//! ```rust
+//! use dhcproto::{v4, v6::{self, dhcp4o6::{self, Dhcp4o6Flags}}};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // client
+//! let mut discover = v4::Message::default();
+//! discover.opts_mut().insert(v4::DhcpOption::MessageType(v4::MessageType::Discover));
+//! let query = dhcp4o6::query(discover.clone(), Dhcp4o6Flags::default());
+//!
+//! // server
+//! let (msg, flags) = dhcp4o6::unwrap(&query).unwrap();
+//! assert_eq!(msg, &discover);
+//! assert!(!flags.unicast());
+//! let mut offer = v4::Message::default();
+//! offer.set_opcode(v4::Opcode::BootReply).set_xid(msg.xid());
+//! let response = dhcp4o6::response(offer);
+//! assert_eq!(response.msg_type(), v6::MessageType::DHCPv4Response);
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 7341]: https://datatracker.ietf.org/doc/html/rfc7341
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    v4,
+    v6::{DhcpOption, Message, MessageType, OptionCode},
+};
+
+/// DHCPv4-QUERY flags, sent where other messages hold the transaction id
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Dhcp4o6Flags([u8; 3]);
+
+impl fmt::Debug for Dhcp4o6Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dhcp4o6Flags")
+            .field("unicast", &self.unicast())
+            .finish()
+    }
+}
+
+impl fmt::Display for Dhcp4o6Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl Dhcp4o6Flags {
+    /// get the status of the unicast flag, set when the client would have
+    /// unicast the DHCPv4 message (renewing)
+    pub fn unicast(&self) -> bool {
+        self.0[0] & 0x80 != 0
+    }
+    /// set the unicast bit, returns a new Dhcp4o6Flags
+    pub fn set_unicast(mut self) -> Self {
+        self.0[0] |= 0x80;
+        self
+    }
+    /// the flags of a DHCPv4-QUERY
+    pub fn from_message(msg: &Message) -> Self {
+        Self(msg.xid())
+    }
+}
+
+impl From<[u8; 3]> for Dhcp4o6Flags {
+    fn from(n: [u8; 3]) -> Self {
+        Self(n)
+    }
+}
+
+impl From<Dhcp4o6Flags> for [u8; 3] {
+    fn from(f: Dhcp4o6Flags) -> Self {
+        f.0
+    }
+}
+
+/// wrap a client's DHCPv4 message in a `DHCPV4-QUERY`
+pub fn query(msg: v4::Message, flags: Dhcp4o6Flags) -> Message {
+    wrap(MessageType::DHCPv4Query, msg, flags)
+}
+
+/// wrap the server's DHCPv4 reply in a `DHCPV4-RESPONSE`, its flags are 0
+pub fn response(msg: v4::Message) -> Message {
+    wrap(MessageType::DHCPv4Response, msg, Dhcp4o6Flags::default())
+}
+
+fn wrap(msg_type: MessageType, msg: v4::Message, flags: Dhcp4o6Flags) -> Message {
+    let mut wrapped = Message::new_with_id(msg_type, flags.into());
+    wrapped
+        .opts_mut()
+        .insert(DhcpOption::Dhcpv4Msg(Box::new(msg)));
+    wrapped
+}
+
+/// the DHCPv4 message & flags of a `DHCPV4-QUERY` or `DHCPV4-RESPONSE`.
+/// Returns `None` for other messages, if there is no DHCPv4 message, or if
+/// it is a `BOOTREPLY` in a query or a `BOOTREQUEST` in a response, which
+/// RFC 7341 says to discard
+pub fn unwrap(msg: &Message) -> Option<(&v4::Message, Dhcp4o6Flags)> {
+    let opcode = match msg.msg_type() {
+        MessageType::DHCPv4Query => v4::Opcode::BootRequest,
+        MessageType::DHCPv4Response => v4::Opcode::BootReply,
+        _ => return None,
+    };
+    match msg.opts().get(OptionCode::Dhcpv4Msg)? {
+        DhcpOption::Dhcpv4Msg(inner) if inner.opcode() == opcode => {
+            Some((inner, Dhcp4o6Flags::from_message(msg)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decoder::{Decodable, Decoder},
+        encoder::{Encodable, Encoder},
+    };
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn roundtrip(msg: &Message) -> Result<Message> {
+        let mut buf = Vec::new();
+        msg.encode(&mut Encoder::new(&mut buf))?;
+        Ok(Message::decode(&mut Decoder::new(&buf))?)
+    }
+
+    #[test]
+    fn test_flags() {
+        let flags = Dhcp4o6Flags::default();
+        assert!(!flags.unicast());
+        let flags = flags.set_unicast();
+        assert!(flags.unicast());
+        assert_eq!(<[u8; 3]>::from(flags), [0x80, 0, 0]);
+        assert_eq!(
+            Dhcp4o6Flags::from([0x81, 0, 1]).set_unicast().0,
+            [0x81, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_wrap_unwrap() -> Result<()> {
+        let mut request = v4::Message::default();
+        request
+            .set_ciaddr([192, 168, 0, 10])
+            .opts_mut()
+            .insert(v4::DhcpOption::MessageType(v4::MessageType::Request));
+        let query = roundtrip(&query(
+            request.clone(),
+            Dhcp4o6Flags::default().set_unicast(),
+        ))?;
+        assert_eq!(query.msg_type(), MessageType::DHCPv4Query);
+        assert_eq!(query.xid(), [0x80, 0, 0]);
+        let (msg, flags) = unwrap(&query).unwrap();
+        assert_eq!(msg, &request);
+        assert!(flags.unicast());
+
+        let mut ack = v4::Message::default();
+        ack.set_opcode(v4::Opcode::BootReply)
+            .set_xid(request.xid())
+            .opts_mut()
+            .insert(v4::DhcpOption::MessageType(v4::MessageType::Ack));
+        let response = roundtrip(&response(ack.clone()))?;
+        assert_eq!(response.xid(), [0, 0, 0]);
+        assert_eq!(unwrap(&response), Some((&ack, Dhcp4o6Flags::default())));
+
+        // wrong direction, wrong message type or nothing inside
+        assert_eq!(unwrap(&super::query(ack, Dhcp4o6Flags::default())), None);
+        assert_eq!(unwrap(&super::response(request)), None);
+        assert_eq!(unwrap(&Message::new(MessageType::Reply)), None);
+        assert_eq!(unwrap(&Message::new(MessageType::DHCPv4Query)), None);
+
+        // 4o6 servers option
+        let mut opts = crate::v6::DhcpOptions::new();
+        opts.insert(DhcpOption::Dhcp4ODhcp6Server(vec!["2001:db8::1".parse()?]));
+        let mut buf = Vec::new();
+        opts.encode(&mut Encoder::new(&mut buf))?;
+        assert_eq!(&buf[..4], &[0, 88, 0, 16]);
+        assert_eq!(
+            crate::v6::DhcpOptions::decode(&mut Decoder::new(&buf))?,
+            opts
+        );
+        Ok(())
+    }
+}
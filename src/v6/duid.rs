@@ -3,11 +3,14 @@ use std::net::Ipv6Addr;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{v4::HType, Encoder};
+use crate::{
+    v4::{HType, HardwareAddr},
+    Encoder,
+};
 
 /// Duid helper type
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Duid(Vec<u8>);
 // TODO: define specific duid types
 
@@ -40,6 +43,36 @@ impl Duid {
         e.write_u128(addr.into()).unwrap();
         Self(buf)
     }
+    /// new DUID link layer address with time, from a [`HardwareAddr`]
+    pub fn link_layer_time_addr(time: u32, addr: &HardwareAddr) -> Self {
+        let mut buf = Vec::new();
+        let mut e = Encoder::new(&mut buf);
+        e.write_u16(1).unwrap(); // duid type
+        e.write_u16(u8::from(addr.htype()) as u16).unwrap();
+        e.write_u32(time).unwrap();
+        e.write_slice(addr.as_bytes()).unwrap();
+        Self(buf)
+    }
+    /// new link layer DUID, from a [`HardwareAddr`]
+    pub fn link_layer_addr(addr: &HardwareAddr) -> Self {
+        let mut buf = Vec::new();
+        let mut e = Encoder::new(&mut buf);
+        e.write_u16(3).unwrap(); // duid type
+        e.write_u16(u8::from(addr.htype()) as u16).unwrap();
+        e.write_slice(addr.as_bytes()).unwrap();
+        Self(buf)
+    }
+    /// the hardware address of a link layer (with or without time) DUID.
+    /// returns `None` for other DUID types, or if the address is not valid for its htype
+    pub fn hw_addr(&self) -> Option<HardwareAddr> {
+        let (htype, addr) = match &self.0[..] {
+            [0, 1, hi, lo, _, _, _, _, addr @ ..] | [0, 3, hi, lo, addr @ ..] => {
+                (u16::from_be_bytes([*hi, *lo]), addr)
+            }
+            _ => return None,
+        };
+        HardwareAddr::new(u8::try_from(htype).ok()?.into(), addr).ok()
+    }
     /// new DUID-UUID
     /// `uuid` must be 16 bytes long
     pub fn uuid(uuid: &[u8]) -> Self {
@@ -75,3 +108,22 @@ impl From<Vec<u8>> for Duid {
         Self(v)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duid_hw_addr() {
+        let addr = HardwareAddr::Eui48([1, 2, 3, 4, 5, 6]);
+        let duid = Duid::link_layer_addr(&addr);
+        assert_eq!(duid.as_ref(), &[0, 3, 0, 1, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(duid.hw_addr(), Some(addr.clone()));
+
+        let duid = Duid::link_layer_time_addr(0xAABBCCDD, &addr);
+        assert_eq!(&duid.as_ref()[4..8], &[0xAA, 0xBB, 0xCC, 0xDD]);
+        assert_eq!(duid.hw_addr(), Some(addr));
+
+        assert_eq!(Duid::enterprise(1, &[1, 2, 3]).hw_addr(), None);
+    }
+}
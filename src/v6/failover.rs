@@ -0,0 +1,364 @@
+//! DHCPv6 failover ([RFC 8156])
+//!
+//! Failover partners talk over a TCP connection, each message framed with
+//! [`encode_frame`] & [`FrameDecoder`] like bulk leasequery. The messages
+//! use the usual client/server format with the failover [`MessageType`]s
+//! (`BNDUPD`, `CONNECT`, `STATE`, ...) and options (`OPTION_F_*`).
+//! [`Connect`] & [`State`] build and read the messages that set up a
+//! connection and exchange server state.
+//!
+//! ```rust
+//! use dhcproto::v6::{failover::{Connect, FrameDecoder, ServerState, State, encode_frame}, Message, MessageType};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let server_id = vec![0, 3, 0, 1, 1, 2, 3, 4, 5, 6];
+//! let connect = Connect {
+//!     relationship_name: "pair-1".into(),
+//!     max_unacked_bndupd: 100,
+//!     keepalive_time: 60,
+//!     mclt: Some(3600),
+//! };
+//! let state = State { state: ServerState::Normal, start_time: 1_700_000_000, flags: 0 };
+//!
+//! let mut frames = FrameDecoder::new();
+//! frames.extend(&encode_frame(&connect.to_message(&server_id))?);
+//! frames.extend(&encode_frame(&state.to_message(&server_id))?);
+//!
+//! let msg = frames.next_message::<Message>()?.unwrap();
+//! assert_eq!(msg.msg_type(), MessageType::Connect);
+//! assert_eq!(Connect::from_message(&msg), Some(connect));
+//! let msg = frames.next_message::<Message>()?.unwrap();
+//! assert_eq!(State::from_message(&msg), Some(state));
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 8156]: https://datatracker.ietf.org/doc/html/rfc8156
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::framing::{encode_frame, FrameDecoder};
+use crate::{
+    decoder::{Decodable, Decoder},
+    error::DecodeResult,
+    v6::{DhcpOption, DhcpOptions, Message, MessageType, OptionCode},
+};
+
+/// failover protocol version sent in `CONNECT`
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Binding status of a lease, sent in `OPTION_F_BINDING_STATUS` (114)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BindingStatus {
+    /// 1
+    Active,
+    /// 2
+    Expired,
+    /// 3
+    Released,
+    /// 4
+    PendingFree,
+    /// 5
+    PendingDelete,
+    /// 6
+    Free,
+    /// 7
+    FreeBackup,
+    /// 8
+    Abandoned,
+    /// 9
+    Reset,
+    /// unknown/unimplemented status
+    Unknown(u8),
+}
+
+impl From<u8> for BindingStatus {
+    fn from(n: u8) -> Self {
+        use BindingStatus::*;
+        match n {
+            1 => Active,
+            2 => Expired,
+            3 => Released,
+            4 => PendingFree,
+            5 => PendingDelete,
+            6 => Free,
+            7 => FreeBackup,
+            8 => Abandoned,
+            9 => Reset,
+            _ => Unknown(n),
+        }
+    }
+}
+
+impl From<BindingStatus> for u8 {
+    fn from(status: BindingStatus) -> Self {
+        use BindingStatus::*;
+        match status {
+            Active => 1,
+            Expired => 2,
+            Released => 3,
+            PendingFree => 4,
+            PendingDelete => 5,
+            Free => 6,
+            FreeBackup => 7,
+            Abandoned => 8,
+            Reset => 9,
+            Unknown(n) => n,
+        }
+    }
+}
+
+/// Failover state of a server, sent in `OPTION_F_SERVER_STATE` (132)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerState {
+    /// 1
+    Startup,
+    /// 2
+    Normal,
+    /// 3
+    CommunicationsInterrupted,
+    /// 4
+    PartnerDown,
+    /// 5
+    PotentialConflict,
+    /// 6
+    Recover,
+    /// 7
+    Paused,
+    /// 8
+    Shutdown,
+    /// 9
+    RecoverDone,
+    /// 10
+    ResolutionInterrupted,
+    /// 11
+    ConflictDone,
+    /// 12
+    RecoverWait,
+    /// unknown/unimplemented state
+    Unknown(u8),
+}
+
+impl From<u8> for ServerState {
+    fn from(n: u8) -> Self {
+        use ServerState::*;
+        match n {
+            1 => Startup,
+            2 => Normal,
+            3 => CommunicationsInterrupted,
+            4 => PartnerDown,
+            5 => PotentialConflict,
+            6 => Recover,
+            7 => Paused,
+            8 => Shutdown,
+            9 => RecoverDone,
+            10 => ResolutionInterrupted,
+            11 => ConflictDone,
+            12 => RecoverWait,
+            _ => Unknown(n),
+        }
+    }
+}
+
+impl From<ServerState> for u8 {
+    fn from(state: ServerState) -> Self {
+        use ServerState::*;
+        match state {
+            Startup => 1,
+            Normal => 2,
+            CommunicationsInterrupted => 3,
+            PartnerDown => 4,
+            PotentialConflict => 5,
+            Recover => 6,
+            Paused => 7,
+            Shutdown => 8,
+            RecoverDone => 9,
+            ResolutionInterrupted => 10,
+            ConflictDone => 11,
+            RecoverWait => 12,
+            Unknown(n) => n,
+        }
+    }
+}
+
+/// Reconfigure key of a client & when it was last used, sent in
+/// `OPTION_F_RECONFIGURE_DATA` (129)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReconfigureData {
+    /// time the last Reconfigure was sent to the client
+    pub time: u32,
+    /// the client's reconfigure key
+    pub key: Vec<u8>,
+}
+
+impl Decodable for ReconfigureData {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(ReconfigureData {
+            time: decoder.field("time", |d| d.read_u32())?,
+            key: decoder.field("key", |d| {
+                let len = d.buffer().len();
+                Ok(d.read_slice(len)?.to_vec())
+            })?,
+        })
+    }
+}
+
+/// Contents of a `CONNECT`, the first message a server sends its partner
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Connect {
+    /// name of the failover relationship
+    pub relationship_name: String,
+    /// most `BNDUPD`s the sender accepts before it has replied
+    pub max_unacked_bndupd: u32,
+    /// seconds between `CONTACT` messages
+    pub keepalive_time: u32,
+    /// maximum client lead time, sent by the primary server
+    pub mclt: Option<u32>,
+}
+
+impl Connect {
+    /// build a `CONNECT` from the server with DUID `server_id`
+    pub fn to_message(&self, server_id: &[u8]) -> Message {
+        let mut msg = Message::new(MessageType::Connect);
+        let opts = msg.opts_mut();
+        opts.insert(DhcpOption::ServerId(server_id.to_vec()));
+        opts.insert(DhcpOption::FProtocolVersion(PROTOCOL_VERSION));
+        opts.insert(DhcpOption::FMaxUnackedBndupd(self.max_unacked_bndupd));
+        opts.insert(DhcpOption::FKeepaliveTime(self.keepalive_time));
+        opts.insert(DhcpOption::FRelationshipName(
+            self.relationship_name.clone(),
+        ));
+        if let Some(mclt) = self.mclt {
+            opts.insert(DhcpOption::FMclt(mclt));
+        }
+        msg
+    }
+    /// read a `CONNECT`. Returns `None` for other messages or if a required
+    /// option is missing
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        if msg.msg_type() != MessageType::Connect {
+            return None;
+        }
+        let opts = msg.opts();
+        Some(Connect {
+            relationship_name: match opts.get(OptionCode::FRelationshipName)? {
+                DhcpOption::FRelationshipName(name) => name.clone(),
+                _ => return None,
+            },
+            max_unacked_bndupd: match opts.get(OptionCode::FMaxUnackedBndupd)? {
+                DhcpOption::FMaxUnackedBndupd(n) => *n,
+                _ => return None,
+            },
+            keepalive_time: match opts.get(OptionCode::FKeepaliveTime)? {
+                DhcpOption::FKeepaliveTime(secs) => *secs,
+                _ => return None,
+            },
+            mclt: match opts.get(OptionCode::FMclt) {
+                Some(DhcpOption::FMclt(secs)) => Some(*secs),
+                _ => None,
+            },
+        })
+    }
+}
+
+/// Contents of a `STATE`, sent when a server's failover state changes
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct State {
+    /// the sender's state
+    pub state: ServerState,
+    /// time the sender entered `state`
+    pub start_time: u32,
+    /// server flags
+    pub flags: u8,
+}
+
+impl State {
+    /// build a `STATE` from the server with DUID `server_id`
+    pub fn to_message(&self, server_id: &[u8]) -> Message {
+        let mut msg = Message::new(MessageType::State);
+        let opts = msg.opts_mut();
+        opts.insert(DhcpOption::ServerId(server_id.to_vec()));
+        opts.insert(DhcpOption::FServerFlags(self.flags));
+        opts.insert(DhcpOption::FServerState(self.state));
+        opts.insert(DhcpOption::FStartTimeOfState(self.start_time));
+        msg
+    }
+    /// read a `STATE`. Returns `None` for other messages or if the state
+    /// or its start time is missing. Missing flags are 0
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        if msg.msg_type() != MessageType::State {
+            return None;
+        }
+        let opts = msg.opts();
+        Some(State {
+            state: match opts.get(OptionCode::FServerState)? {
+                DhcpOption::FServerState(state) => *state,
+                _ => return None,
+            },
+            start_time: match opts.get(OptionCode::FStartTimeOfState)? {
+                DhcpOption::FStartTimeOfState(time) => *time,
+                _ => return None,
+            },
+            flags: match opts.get(OptionCode::FServerFlags) {
+                Some(DhcpOption::FServerFlags(flags)) => *flags,
+                _ => 0,
+            },
+        })
+    }
+}
+
+/// the DNS removal info of a lease from `OPTION_F_DNS_REMOVAL_INFO` (116),
+/// which holds the host name, zone name & DNS flags options
+pub fn dns_removal_info(opts: &DhcpOptions) -> Option<&DhcpOptions> {
+    match opts.get(OptionCode::Fdnsremovalinfo)? {
+        DhcpOption::Fdnsremovalinfo(info) => Some(info),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_states() {
+        for n in 0..=13 {
+            assert_eq!(u8::from(ServerState::from(n)), n);
+            assert_eq!(u8::from(BindingStatus::from(n)), n);
+        }
+        assert_eq!(ServerState::from(4), ServerState::PartnerDown);
+        assert_eq!(BindingStatus::from(0), BindingStatus::Unknown(0));
+    }
+
+    #[test]
+    fn test_connect_state() {
+        let server_id = [0, 3, 0, 1, 1, 2, 3, 4, 5, 6];
+        let connect = Connect {
+            relationship_name: "pair-1".into(),
+            max_unacked_bndupd: 10,
+            keepalive_time: 30,
+            mclt: None,
+        };
+        let msg = connect.to_message(&server_id);
+        assert_eq!(
+            msg.opts().get(OptionCode::FProtocolVersion),
+            Some(&DhcpOption::FProtocolVersion(PROTOCOL_VERSION))
+        );
+        assert_eq!(Connect::from_message(&msg), Some(connect));
+
+        let mut msg = State {
+            state: ServerState::PartnerDown,
+            start_time: 100,
+            flags: 0,
+        }
+        .to_message(&server_id);
+        assert_eq!(Connect::from_message(&msg), None);
+        msg.opts_mut().remove(OptionCode::FServerFlags);
+        assert_eq!(State::from_message(&msg).map(|s| s.flags), Some(0));
+        msg.opts_mut().remove(OptionCode::FStartTimeOfState);
+        assert_eq!(State::from_message(&msg), None);
+    }
+}
@@ -0,0 +1,255 @@
+//! DHCPv6 leasequery ([RFC 5007]) and bulk leasequery ([RFC 5460])
+//!
+//! A requestor sends a `LEASEQUERY` holding an [`LqQuery`] option, the
+//! server answers with a `LEASEQUERY-REPLY` holding [`ClientData`] (or, for
+//! bulk leasequery over TCP, one `LEASEQUERY-DATA` per additional client and
+//! a final `LEASEQUERY-DONE`). Bulk leasequery messages are framed with
+//! [`encode_frame`] and [`FrameDecoder`].
+//!
+//! ```rust
+//! use dhcproto::v6::{leasequery::{LeaseQuery, FrameDecoder, encode_frame}, Message};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let query = LeaseQuery::Address {
+//!     addr: "2001:db8::10".parse()?,
+//!     link: "2001:db8::".parse()?,
+//! };
+//! let msg = query.to_message(&[0, 3, 0, 1, 1, 2, 3, 4, 5, 6]);
+//!
+//! // over TCP
+//! let mut frames = FrameDecoder::new();
+//! frames.extend(&encode_frame(&msg)?);
+//! let msg = frames.next_message::<Message>()?.unwrap();
+//! assert_eq!(LeaseQuery::from_message(&msg), Some(query));
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 5007]: https://datatracker.ietf.org/doc/html/rfc5007
+//! [RFC 5460]: https://datatracker.ietf.org/doc/html/rfc5460
+//! [`ClientData`]: crate::v6::ClientData
+use std::net::Ipv6Addr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::framing::{encode_frame, FrameDecoder};
+use crate::v6::{
+    DhcpOption, DhcpOptions, IAAddr, LqQuery, Message, MessageType, OptionCode, QueryType, RemoteId,
+};
+
+/// What a leasequery asks about. `link` is the link address to search on,
+/// unspecified searches all links
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaseQuery {
+    /// query by leased address or delegated prefix address
+    Address {
+        /// the leased address, or an address in the delegated prefix
+        addr: Ipv6Addr,
+        /// link to search on
+        link: Ipv6Addr,
+    },
+    /// query by client DUID
+    ClientId {
+        /// the client's DUID
+        duid: Vec<u8>,
+        /// link to search on
+        link: Ipv6Addr,
+    },
+    /// query by relay agent DUID, bulk leasequery only
+    RelayId {
+        /// the relay agent's DUID
+        duid: Vec<u8>,
+        /// link to search on
+        link: Ipv6Addr,
+    },
+    /// query for all bindings on a link, bulk leasequery only
+    LinkAddress(Ipv6Addr),
+    /// query by relay agent remote-id, bulk leasequery only
+    RemoteId {
+        /// enterprise number of the remote-id
+        enterprise: u32,
+        /// the remote-id
+        remote_id: Vec<u8>,
+        /// link to search on
+        link: Ipv6Addr,
+    },
+}
+
+impl LeaseQuery {
+    /// the query type sent in the [`LqQuery`] option
+    pub fn qtype(&self) -> QueryType {
+        match self {
+            LeaseQuery::Address { .. } => QueryType::Address,
+            LeaseQuery::ClientId { .. } => QueryType::ClientId,
+            LeaseQuery::RelayId { .. } => QueryType::RelayId,
+            LeaseQuery::LinkAddress(_) => QueryType::LinkAddress,
+            LeaseQuery::RemoteId { .. } => QueryType::RemoteId,
+        }
+    }
+    /// build the [`LqQuery`] option
+    pub fn to_option(&self) -> LqQuery {
+        let mut opts = DhcpOptions::new();
+        let link_address = match self {
+            LeaseQuery::Address { addr, link } => {
+                opts.insert(DhcpOption::IAAddr(IAAddr {
+                    addr: *addr,
+                    preferred_life: 0,
+                    valid_life: 0,
+                    opts: DhcpOptions::new(),
+                }));
+                *link
+            }
+            LeaseQuery::ClientId { duid, link } => {
+                opts.insert(DhcpOption::ClientId(duid.clone()));
+                *link
+            }
+            LeaseQuery::RelayId { duid, link } => {
+                opts.insert(DhcpOption::RelayId(duid.clone()));
+                *link
+            }
+            LeaseQuery::LinkAddress(link) => *link,
+            LeaseQuery::RemoteId {
+                enterprise,
+                remote_id,
+                link,
+            } => {
+                opts.insert(DhcpOption::RemoteId(RemoteId {
+                    enterprise: *enterprise,
+                    id: remote_id.clone(),
+                }));
+                *link
+            }
+        };
+        LqQuery {
+            qtype: self.qtype(),
+            link_address,
+            opts,
+        }
+    }
+    /// read a query from an [`LqQuery`] option. Returns `None` for unknown
+    /// query types or if the option the query type needs is missing
+    pub fn from_option(query: &LqQuery) -> Option<Self> {
+        let link = query.link_address;
+        Some(match query.qtype {
+            QueryType::Address => match query.opts.get(OptionCode::IAAddr)? {
+                DhcpOption::IAAddr(addr) => LeaseQuery::Address {
+                    addr: addr.addr,
+                    link,
+                },
+                _ => return None,
+            },
+            QueryType::ClientId => match query.opts.get(OptionCode::ClientId)? {
+                DhcpOption::ClientId(duid) => LeaseQuery::ClientId {
+                    duid: duid.clone(),
+                    link,
+                },
+                _ => return None,
+            },
+            QueryType::RelayId => match query.opts.get(OptionCode::RelayId)? {
+                DhcpOption::RelayId(duid) => LeaseQuery::RelayId {
+                    duid: duid.clone(),
+                    link,
+                },
+                _ => return None,
+            },
+            QueryType::LinkAddress => LeaseQuery::LinkAddress(link),
+            QueryType::RemoteId => match query.opts.get(OptionCode::RemoteId)? {
+                DhcpOption::RemoteId(remote_id) => LeaseQuery::RemoteId {
+                    enterprise: remote_id.enterprise,
+                    remote_id: remote_id.id.clone(),
+                    link,
+                },
+                _ => return None,
+            },
+            QueryType::Unknown(_) => return None,
+        })
+    }
+    /// build a `LEASEQUERY` with a random xid, identifying the requestor
+    /// with `requestor_duid`
+    pub fn to_message(&self, requestor_duid: &[u8]) -> Message {
+        let mut msg = Message::new(MessageType::LeaseQuery);
+        let opts = msg.opts_mut();
+        opts.insert(DhcpOption::ClientId(requestor_duid.to_vec()));
+        opts.insert(DhcpOption::LqQuery(self.to_option()));
+        msg
+    }
+    /// parse a `LEASEQUERY`. Returns `None` if `msg` isn't a leasequery or
+    /// its query can't be read
+    pub fn from_message(msg: &Message) -> Option<Self> {
+        if msg.msg_type() != MessageType::LeaseQuery {
+            return None;
+        }
+        match msg.opts().get(OptionCode::LqQuery)? {
+            DhcpOption::LqQuery(query) => Self::from_option(query),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    #[test]
+    fn test_queries() -> Result<()> {
+        let link: Ipv6Addr = "2001:db8::".parse()?;
+        let duid = vec![0, 3, 0, 1, 1, 2, 3, 4, 5, 6];
+        let queries = [
+            LeaseQuery::Address {
+                addr: "2001:db8::10".parse()?,
+                link,
+            },
+            LeaseQuery::ClientId {
+                duid: duid.clone(),
+                link: Ipv6Addr::UNSPECIFIED,
+            },
+            LeaseQuery::RelayId {
+                duid: vec![0, 2, 0, 0, 0, 9, 1],
+                link,
+            },
+            LeaseQuery::LinkAddress(link),
+            LeaseQuery::RemoteId {
+                enterprise: 3561,
+                remote_id: b"port-1".to_vec(),
+                link,
+            },
+        ];
+
+        let mut frames = FrameDecoder::new();
+        for query in &queries {
+            let msg = query.to_message(&duid);
+            assert!(msg.opts().get(OptionCode::ClientId).is_some());
+            frames.extend(&encode_frame(&msg)?);
+        }
+        for query in &queries {
+            let msg = frames.next_message::<Message>()?.unwrap();
+            assert_eq!(msg.msg_type(), MessageType::LeaseQuery);
+            assert_eq!(LeaseQuery::from_message(&msg).as_ref(), Some(query));
+        }
+        assert!(frames.next_message::<Message>()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_query() {
+        // address query without an IAAddr
+        let query = LqQuery {
+            qtype: QueryType::Address,
+            link_address: Ipv6Addr::UNSPECIFIED,
+            opts: DhcpOptions::new(),
+        };
+        assert_eq!(LeaseQuery::from_option(&query), None);
+        let query = LqQuery {
+            qtype: QueryType::Unknown(9),
+            ..query
+        };
+        assert_eq!(LeaseQuery::from_option(&query), None);
+        // not a leasequery
+        assert_eq!(
+            LeaseQuery::from_message(&Message::new(MessageType::Solicit)),
+            None
+        );
+    }
+}
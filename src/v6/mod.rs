@@ -52,16 +52,26 @@
 //! # Ok(()) }
 //! ```
 //!
+pub mod binding;
+pub mod dhcp4o6;
 pub mod duid;
+pub mod failover;
+pub mod leasequery;
 mod option_codes;
 mod options;
 mod oro_codes;
+pub mod pd;
+pub mod s46;
+pub mod timers;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use std::{convert::TryInto, fmt, net::Ipv6Addr};
 
+use self::binding::{IaBinding, LeaseSummary};
+use crate::v4::Architecture;
+
 // re-export submodules from v6
 pub use self::option_codes::*;
 pub use self::options::*;
@@ -212,6 +222,38 @@ impl Message {
     pub fn opts_mut(&mut self) -> &mut DhcpOptions {
         &mut self.opts
     }
+
+    /// every IA_NA, IA_TA & IA_PD in the message, in order
+    pub fn bindings(&self) -> Vec<IaBinding> {
+        self.opts
+            .iter()
+            .filter_map(IaBinding::from_option)
+            .collect()
+    }
+
+    /// validate the IAs of the message & sort their leases, see
+    /// [`LeaseSummary`]
+    pub fn lease_summary(&self) -> LeaseSummary {
+        LeaseSummary::new(binding::status(&self.opts), &self.bindings())
+    }
+
+    /// the boot file URL to send this client. The first architecture in the
+    /// client's Client Architecture Type (61) option with an entry in `urls`
+    /// wins, as clients list them most preferred first. Returns `None` if the
+    /// client sent no architectures or none of them are in `urls`
+    pub fn select_boot_url<'a>(
+        &self,
+        urls: &'a [(Architecture, url::Url)],
+    ) -> Option<&'a url::Url> {
+        match self.opts.get(OptionCode::ClientArchType)? {
+            DhcpOption::ClientArchType(archs) => archs.iter().find_map(|arch| {
+                urls.iter()
+                    .find(|(candidate, _)| candidate == arch)
+                    .map(|(_, url)| url)
+            }),
+            _ => None,
+        }
+    }
 }
 
 /// DHCPv6 message types
@@ -266,6 +308,31 @@ pub enum MessageType {
     DHCPv4Query,
     /// dhcpv4 response - <https://datatracker.ietf.org/doc/html/rfc7341#section-6.2>
     DHCPv4Response,
+    // RFC 8156
+    /// failover binding update - <https://datatracker.ietf.org/doc/html/rfc8156>
+    BndUpd,
+    /// failover binding update reply - <https://datatracker.ietf.org/doc/html/rfc8156>
+    BndReply,
+    /// failover pool request - <https://datatracker.ietf.org/doc/html/rfc8156>
+    PoolReq,
+    /// failover pool response - <https://datatracker.ietf.org/doc/html/rfc8156>
+    PoolResp,
+    /// failover update request - <https://datatracker.ietf.org/doc/html/rfc8156>
+    UpdReq,
+    /// failover update request all - <https://datatracker.ietf.org/doc/html/rfc8156>
+    UpdReqAll,
+    /// failover update done - <https://datatracker.ietf.org/doc/html/rfc8156>
+    UpdDone,
+    /// failover connect - <https://datatracker.ietf.org/doc/html/rfc8156>
+    Connect,
+    /// failover connect reply - <https://datatracker.ietf.org/doc/html/rfc8156>
+    ConnectReply,
+    /// failover disconnect - <https://datatracker.ietf.org/doc/html/rfc8156>
+    Disconnect,
+    /// failover state - <https://datatracker.ietf.org/doc/html/rfc8156>
+    State,
+    /// failover contact - <https://datatracker.ietf.org/doc/html/rfc8156>
+    Contact,
     /// unknown/unimplemented message type
     Unknown(u8),
 }
@@ -300,6 +367,18 @@ impl From<u8> for MessageType {
             // RFC 7341
             20 => DHCPv4Query,
             21 => DHCPv4Response,
+            24 => BndUpd,
+            25 => BndReply,
+            26 => PoolReq,
+            27 => PoolResp,
+            28 => UpdReq,
+            29 => UpdReqAll,
+            30 => UpdDone,
+            31 => Connect,
+            32 => ConnectReply,
+            33 => Disconnect,
+            34 => State,
+            35 => Contact,
             n => Unknown(n),
         }
     }
@@ -335,6 +414,18 @@ impl From<MessageType> for u8 {
             // RFC 7341
             DHCPv4Query => 20,
             DHCPv4Response => 21,
+            BndUpd => 24,
+            BndReply => 25,
+            PoolReq => 26,
+            PoolResp => 27,
+            UpdReq => 28,
+            UpdReqAll => 29,
+            UpdDone => 30,
+            Connect => 31,
+            ConnectReply => 32,
+            Disconnect => 33,
+            State => 34,
+            Contact => 35,
             Unknown(n) => n,
         }
     }
@@ -343,9 +434,9 @@ impl From<MessageType> for u8 {
 impl Decodable for Message {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(Message {
-            msg_type: decoder.read_u8()?.into(),
-            xid: decoder.read::<3>()?,
-            opts: DhcpOptions::decode(decoder)?,
+            msg_type: decoder.field("msg_type", |d| d.read_u8())?.into(),
+            xid: decoder.field("xid", |d| d.read::<3>())?,
+            opts: decoder.field("opts", DhcpOptions::decode)?,
         })
     }
 }
@@ -417,16 +508,63 @@ impl RelayMessage {
     pub fn opts_mut(&mut self) -> &mut DhcpOptions {
         &mut self.opts
     }
+
+    /// the first-hop relay of a chain of Relay-Forw messages, the innermost
+    /// one. `self` if its Relay Message option doesn't hold another Relay-Forw
+    pub fn first_hop(&self) -> &RelayMessage {
+        let mut relay = self;
+        while let Some(DhcpOption::RelayMsg(inner)) = relay.opts.get(OptionCode::RelayMsg) {
+            if inner.msg_type() != MessageType::RelayForw {
+                break;
+            }
+            relay = inner;
+        }
+        relay
+    }
+
+    /// the client's link-layer address, from the first-hop relay of the chain.
+    /// Only the first-hop relay can see it, so the option is ignored in other
+    /// relays <https://datatracker.ietf.org/doc/html/rfc6939#section-5>
+    pub fn client_linklayer_addr(&self) -> Option<&ClientLinklayerAddr> {
+        match self.first_hop().opts.get(OptionCode::ClientLinklayerAddr)? {
+            DhcpOption::ClientLinklayerAddr(addr) => Some(addr),
+            _ => None,
+        }
+    }
+
+    /// the options a server copies from this Relay-Forw into its Relay-Reply:
+    /// the Interface-Id, which is always echoed, & any option listed in the
+    /// relay's Echo Request option (43) except the Relay Message & ERO
+    /// themselves. <https://datatracker.ietf.org/doc/html/rfc4994#section-5>
+    pub fn echo_opts(&self) -> DhcpOptions {
+        let requested = match self.opts.get(OptionCode::ERO) {
+            Some(DhcpOption::ERO(codes)) => codes.as_slice(),
+            _ => &[],
+        };
+        let mut opts = DhcpOptions::new();
+        for opt in self.opts.iter() {
+            let code = OptionCode::from(opt);
+            let echo = match code {
+                OptionCode::InterfaceId => true,
+                OptionCode::RelayMsg | OptionCode::ERO => false,
+                _ => requested.contains(&code),
+            };
+            if echo {
+                opts.insert(opt.clone());
+            }
+        }
+        opts
+    }
 }
 
 impl Decodable for RelayMessage {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(Self {
-            msg_type: decoder.read_u8()?.into(),
-            hop_count: decoder.read_u8()?,
-            link_addr: decoder.read::<16>()?.into(),
-            peer_addr: decoder.read::<16>()?.into(),
-            opts: DhcpOptions::decode(decoder)?,
+            msg_type: decoder.field("msg_type", |d| d.read_u8())?.into(),
+            hop_count: decoder.field("hop_count", |d| d.read_u8())?,
+            link_addr: decoder.field("link_addr", |d| d.read::<16>())?.into(),
+            peer_addr: decoder.field("peer_addr", |d| d.read::<16>())?.into(),
+            opts: decoder.field("opts", DhcpOptions::decode)?,
         })
     }
 }
@@ -562,6 +700,127 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn test_decode_error_context() {
+        let mut input = vec![
+            0x07, 0x00, 0x00, 0x01, // msg_type, xid
+            0x00, 0x03, 0x00, 0x26, // IANA, len 38
+            0x00, 0x00, 0x00, 0x01, // id
+            0x00, 0x00, 0x00, 0x00, // t1
+            0x00, 0x00, 0x00, 0x00, // t2
+            0x00, 0x05, 0x00, 0x16, // IAAddr, len 22
+        ];
+        input.extend([0; 16]); // addr
+        input.extend([0, 0, 0, 1]); // preferred_life
+        input.extend([0, 1]); // valid_life, truncated
+        let err = Message::decode(&mut Decoder::new(&input)).unwrap_err();
+        assert_eq!(err.offset(), Some(44));
+        assert_eq!(
+            err.path().unwrap().to_string(),
+            "opts[IANA].opts[IAAddr].valid_life"
+        );
+        assert!(matches!(err.kind(), DecodeError::NotEnoughBytes));
+    }
+
+    #[test]
+    fn test_echo_opts() {
+        let mut relay = RelayMessage {
+            msg_type: MessageType::RelayForw,
+            hop_count: 0,
+            link_addr: Ipv6Addr::UNSPECIFIED,
+            peer_addr: Ipv6Addr::UNSPECIFIED,
+            opts: DhcpOptions::new(),
+        };
+        relay
+            .opts_mut()
+            .insert(DhcpOption::InterfaceId(b"eth0".to_vec()));
+        relay.opts_mut().insert(DhcpOption::RemoteId(RemoteId {
+            enterprise: 3561,
+            id: b"port-1".to_vec(),
+        }));
+        relay
+            .opts_mut()
+            .insert(DhcpOption::SubscriberId(b"sub".to_vec()));
+        let inner = relay.clone();
+        relay.opts_mut().insert(DhcpOption::RelayMsg(inner));
+        // without an ERO only the interface-id is echoed
+        let echo = relay.echo_opts();
+        assert_eq!(echo.iter().count(), 1);
+        assert!(echo.get(OptionCode::InterfaceId).is_some());
+
+        relay.opts_mut().insert(DhcpOption::ERO(vec![
+            OptionCode::SubscriberId,
+            OptionCode::RelayMsg,
+            OptionCode::ERO,
+            OptionCode::RelayPort,
+        ]));
+        let echo = relay.echo_opts();
+        assert_eq!(echo.iter().count(), 2);
+        assert_eq!(
+            echo.get(OptionCode::SubscriberId),
+            Some(&DhcpOption::SubscriberId(b"sub".to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_client_linklayer_addr() -> Result<()> {
+        let relay = |opts: Vec<DhcpOption>| {
+            let mut relay = RelayMessage {
+                msg_type: MessageType::RelayForw,
+                hop_count: 0,
+                link_addr: Ipv6Addr::UNSPECIFIED,
+                peer_addr: Ipv6Addr::UNSPECIFIED,
+                opts: DhcpOptions::new(),
+            };
+            for opt in opts {
+                relay.opts_mut().insert(opt);
+            }
+            relay
+        };
+        let mac: crate::v4::HardwareAddr = "de:ad:be:ef:00:01".parse()?;
+        let addr = ClientLinklayerAddr::new(&mac);
+        let first = relay(vec![DhcpOption::ClientLinklayerAddr(addr.clone())]);
+        assert_eq!(first.client_linklayer_addr(), Some(&addr));
+
+        // only the first-hop relay's option is used
+        let other = ClientLinklayerAddr::new(&"00:00:00:00:00:01".parse()?);
+        let mut chain = relay(vec![
+            DhcpOption::ClientLinklayerAddr(other),
+            DhcpOption::RelayMsg(first.clone()),
+        ]);
+        chain = relay(vec![DhcpOption::RelayMsg(chain)]);
+        assert_eq!(chain.first_hop(), &first);
+        assert_eq!(chain.client_linklayer_addr(), Some(&addr));
+        assert_eq!(
+            chain.client_linklayer_addr().and_then(|a| a.hw_addr()),
+            Some(mac)
+        );
+        assert_eq!(relay(vec![]).client_linklayer_addr(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_boot_url() -> Result<()> {
+        let efi: url::Url = "tftp://[2001:db8::1]/bootx64.efi".parse()?;
+        let bios: url::Url = "tftp://[2001:db8::1]/pxelinux.0".parse()?;
+        let urls = [
+            (Architecture::Intelx86PC, bios.clone()),
+            (Architecture::X86_64, efi.clone()),
+        ];
+
+        let mut msg = Message::new(MessageType::Solicit);
+        assert_eq!(msg.select_boot_url(&urls), None);
+        msg.opts_mut().insert(DhcpOption::ClientArchType(vec![
+            Architecture::Unknown(0x10),
+            Architecture::X86_64,
+            Architecture::Intelx86PC,
+        ]));
+        assert_eq!(msg.select_boot_url(&urls), Some(&efi));
+        assert_eq!(msg.select_boot_url(&urls[..1]), Some(&bios));
+        assert_eq!(msg.select_boot_url(&[]), None);
+        Ok(())
+    }
+
     fn reply() -> Vec<u8> {
         vec![
             0x07, 0x49, 0x17, 0x4e, 0x00, 0x19, 0x00, 0x29, 0x27, 0xfe, 0x8f, 0x95, 0x00, 0x00,
@@ -465,20 +465,68 @@ impl From<&DhcpOption> for OptionCode {
             InterfaceId(_) => OptionCode::InterfaceId,
             ReconfMsg(_) => OptionCode::ReconfMsg,
             ReconfAccept => OptionCode::ReconfAccept,
+            SipServerD(_) => OptionCode::SipServerD,
+            SipServerA(_) => OptionCode::SipServerA,
             DomainNameServers(_) => OptionCode::DomainNameServers,
             DomainSearchList(_) => OptionCode::DomainSearchList,
             IAPD(_) => OptionCode::IAPD,
             IAPrefix(_) => OptionCode::IAPrefix,
+            SntpServers(_) => OptionCode::SntpServers,
             InformationRefreshTime(_) => OptionCode::InformationRefreshTime,
-            // SolMaxRt(_) => OptionCode::SolMaxRt,
-            // InfMaxRt(_) => OptionCode::InfMaxRt,
-            // LqQuery(_) => OptionCode::LqQuery,
-            // ClientData(_) => OptionCode::ClientData,
-            // CltTime(_) => OptionCode::CltTime,
-            // LqRelayData(_) => OptionCode::LqRelayData,
-            // LqClientLink(_) => OptionCode::LqClientLink,
-            // RelayId(_) => OptionCode::RelayId,
-            // LinkAddress(_) => OptionCode::LinkAddress,
+            RemoteId(_) => OptionCode::RemoteId,
+            SubscriberId(_) => OptionCode::SubscriberId,
+            ClientFqdn(_) => OptionCode::ClientFqdn,
+            ERO(_) => OptionCode::ERO,
+            LqQuery(_) => OptionCode::LqQuery,
+            ClientData(_) => OptionCode::ClientData,
+            CltTime(_) => OptionCode::CltTime,
+            LqRelayData(_) => OptionCode::LqRelayData,
+            LqClientLink(_) => OptionCode::LqClientLink,
+            RelayId(_) => OptionCode::RelayId,
+            NtpServer(_) => OptionCode::NtpServer,
+            OptBootfileUrl(_) => OptionCode::OptBootfileUrl,
+            OptBootfileParam(_) => OptionCode::OptBootfileParam,
+            ClientArchType(_) => OptionCode::ClientArchType,
+            Nii(_, _, _) => OptionCode::Nii,
+            AftrName(_) => OptionCode::AftrName,
+            Rsoo(_) => OptionCode::Rsoo,
+            PdExclude(_) => OptionCode::PdExclude,
+            Dhcpv4Msg(_) => OptionCode::Dhcpv4Msg,
+            Dhcp4ODhcp6Server(_) => OptionCode::Dhcp4ODhcp6Server,
+            S46Rule(_) => OptionCode::S46Rule,
+            S46Br(_) => OptionCode::S46Br,
+            S46Dmr(_) => OptionCode::S46Dmr,
+            S46V4v6bind(_) => OptionCode::S46V4v6bind,
+            S46Portparams(_) => OptionCode::S46Portparams,
+            S46ContMape(_) => OptionCode::S46ContMape,
+            S46ContMapt(_) => OptionCode::S46ContMapt,
+            S46ContLw(_) => OptionCode::S46ContLw,
+            FBindingStatus(_) => OptionCode::FBindingStatus,
+            FConnectFlags(_) => OptionCode::FConnectFlags,
+            Fdnsremovalinfo(_) => OptionCode::Fdnsremovalinfo,
+            FDNSHostName(_) => OptionCode::FDNSHostName,
+            FDNSZoneName(_) => OptionCode::FDNSZoneName,
+            Fdnsflags(_) => OptionCode::Fdnsflags,
+            Fexpirationtime(_) => OptionCode::Fexpirationtime,
+            FMaxUnackedBndupd(_) => OptionCode::FMaxUnackedBndupd,
+            FMclt(_) => OptionCode::FMclt,
+            FPartnerLifetime(_) => OptionCode::FPartnerLifetime,
+            FPartnerLifetimeSent(_) => OptionCode::FPartnerLifetimeSent,
+            FPartnerDownTime(_) => OptionCode::FPartnerDownTime,
+            FPartnerRawCltTime(_) => OptionCode::FPartnerRawCltTime,
+            FProtocolVersion(_) => OptionCode::FProtocolVersion,
+            FKeepaliveTime(_) => OptionCode::FKeepaliveTime,
+            FReconfigureData(_) => OptionCode::FReconfigureData,
+            FRelationshipName(_) => OptionCode::FRelationshipName,
+            FServerFlags(_) => OptionCode::FServerFlags,
+            FServerState(_) => OptionCode::FServerState,
+            FStartTimeOfState(_) => OptionCode::FStartTimeOfState,
+            FStateExpirationTime(_) => OptionCode::FStateExpirationTime,
+            RelayPort(_) => OptionCode::RelayPort,
+            ClientLinklayerAddr(_) => OptionCode::ClientLinklayerAddr,
+            LinkAddress(_) => OptionCode::LinkAddress,
+            SolMaxRt(_) => OptionCode::SolMaxRt,
+            InfMaxRt(_) => OptionCode::InfMaxRt,
             Unknown(UnknownOption { code, .. }) => OptionCode::Unknown(*code),
         }
     }
@@ -1,17 +1,22 @@
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use trust_dns_proto::{
-    rr::Name,
-    serialize::binary::{BinDecodable, BinDecoder, BinEncodable, BinEncoder},
-};
 
+use ipnet::Ipv6Net;
 use std::{cmp::Ordering, net::Ipv6Addr, ops::RangeInclusive};
 
 use crate::{
     decoder::{Decodable, Decoder},
     encoder::{Encodable, Encoder},
-    error::{DecodeResult, EncodeResult},
-    v6::{MessageType, RelayMessage},
+    error::{DecodeError, DecodeResult, EncodeResult, PathSegment},
+    v4::{
+        self,
+        fqdn::{DdnsPolicy, FqdnFlags},
+    },
+    v6::{
+        failover::{BindingStatus, ReconfigureData, ServerState},
+        s46::{self, S46PortParams, S46Rule, S46V4v6Bind},
+        MessageType, RelayMessage,
+    },
 };
 use crate::{v6::option_codes::OptionCode, Domain};
 
@@ -67,7 +72,7 @@ impl DhcpOptions {
     }
     /// insert a new option into the list of opts
     pub fn insert(&mut self, opt: DhcpOption) {
-        let i = self.0.partition_point(|x| x < &opt);
+        let i = self.0.partition_point(|x| x <= &opt);
         self.0.insert(i, opt)
     }
     /// return a reference to an iterator
@@ -93,7 +98,7 @@ impl IntoIterator for DhcpOptions {
 impl FromIterator<DhcpOption> for DhcpOptions {
     fn from_iter<T: IntoIterator<Item = DhcpOption>>(iter: T) -> Self {
         let mut opts = iter.into_iter().collect::<Vec<_>>();
-        opts.sort_unstable();
+        opts.sort();
         DhcpOptions(opts)
     }
 }
@@ -141,6 +146,10 @@ pub enum DhcpOption {
     ReconfMsg(MessageType),
     /// 20 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.20>
     ReconfAccept,
+    /// 21 - <https://datatracker.ietf.org/doc/html/rfc3319#section-3.1>
+    SipServerD(Vec<Domain>),
+    /// 22 - <https://datatracker.ietf.org/doc/html/rfc3319#section-3.2>
+    SipServerA(Vec<Ipv6Addr>),
     /// 23 - <https://datatracker.ietf.org/doc/html/rfc3646>
     DomainNameServers(Vec<Ipv6Addr>),
     /// 24 - <https://datatracker.ietf.org/doc/html/rfc3646>
@@ -149,16 +158,132 @@ pub enum DhcpOption {
     IAPD(IAPD),
     /// 26 - <https://datatracker.ietf.org/doc/html/rfc3633#section-10>
     IAPrefix(IAPrefix),
+    /// 31 - <https://datatracker.ietf.org/doc/html/rfc4075>
+    SntpServers(Vec<Ipv6Addr>),
+    /// 32 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.23>
     InformationRefreshTime(u32),
-    // SolMaxRt(u32),
-    // InfMaxRt(u32),
-    // LqQuery(_),
-    // ClientData(_),
-    // CltTime(_),
-    // LqRelayData(_),
-    // LqClientLink(_),
-    // RelayId(_),
-    // LinkAddress(_),
+    /// 37 - <https://datatracker.ietf.org/doc/html/rfc4649>
+    RemoteId(RemoteId),
+    /// 38 - <https://datatracker.ietf.org/doc/html/rfc4580>
+    SubscriberId(Vec<u8>),
+    /// 39 - <https://datatracker.ietf.org/doc/html/rfc4704>
+    ClientFqdn(ClientFqdn),
+    /// 43 - <https://datatracker.ietf.org/doc/html/rfc4994>
+    /// options a relay asks the server to echo in its Relay-Reply
+    ERO(Vec<OptionCode>),
+    /// 44 - <https://datatracker.ietf.org/doc/html/rfc5007>
+    LqQuery(LqQuery),
+    /// 45 - <https://datatracker.ietf.org/doc/html/rfc5007>
+    ClientData(ClientData),
+    /// 46 - <https://datatracker.ietf.org/doc/html/rfc5007>
+    /// seconds since the client last talked to the server
+    CltTime(u32),
+    /// 47 - <https://datatracker.ietf.org/doc/html/rfc5007>
+    LqRelayData(LqRelayData),
+    /// 48 - <https://datatracker.ietf.org/doc/html/rfc5007>
+    LqClientLink(Vec<Ipv6Addr>),
+    /// 53 - <https://datatracker.ietf.org/doc/html/rfc5460>
+    /// DUID of a relay agent
+    RelayId(Vec<u8>),
+    /// 56 - <https://datatracker.ietf.org/doc/html/rfc5908>
+    NtpServer(Vec<NtpSuboption>),
+    /// 59 - <https://datatracker.ietf.org/doc/html/rfc5970#section-3.1>
+    OptBootfileUrl(url::Url),
+    /// 60 - <https://datatracker.ietf.org/doc/html/rfc5970#section-3.2>
+    OptBootfileParam(Vec<String>),
+    /// 61 - <https://datatracker.ietf.org/doc/html/rfc5970#section-3.3>
+    /// architectures the client supports, most preferred first
+    ClientArchType(Vec<v4::Architecture>),
+    /// 62 - <https://datatracker.ietf.org/doc/html/rfc5970#section-3.4>
+    /// network interface type, major & minor version
+    Nii(u8, u8, u8),
+    /// 64 - <https://datatracker.ietf.org/doc/html/rfc6334>
+    /// DS-Lite AFTR name
+    AftrName(Domain),
+    /// 66 - <https://datatracker.ietf.org/doc/html/rfc6422>
+    /// options a relay supplies for the server to use
+    Rsoo(DhcpOptions),
+    /// 67 - <https://datatracker.ietf.org/doc/html/rfc6603>
+    PdExclude(PdExclude),
+    /// 79 - <https://datatracker.ietf.org/doc/html/rfc6939>
+    ClientLinklayerAddr(ClientLinklayerAddr),
+    /// 80 - <https://datatracker.ietf.org/doc/html/rfc6977>
+    LinkAddress(Ipv6Addr),
+    /// 82 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.24>
+    SolMaxRt(u32),
+    /// 83 - <https://datatracker.ietf.org/doc/html/rfc8415#section-21.25>
+    InfMaxRt(u32),
+    /// 87 - <https://datatracker.ietf.org/doc/html/rfc7341#section-7.1>
+    /// a whole DHCPv4 message, see [`crate::v6::dhcp4o6`]
+    Dhcpv4Msg(Box<v4::Message>),
+    /// 88 - <https://datatracker.ietf.org/doc/html/rfc7341#section-7.2>
+    /// DHCP 4o6 servers, empty to use the DHCPv6 server
+    Dhcp4ODhcp6Server(Vec<Ipv6Addr>),
+    /// 89 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.1>
+    S46Rule(S46Rule),
+    /// 90 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.2>
+    /// border relay address
+    S46Br(Ipv6Addr),
+    /// 91 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.3>
+    /// default mapping rule prefix, for MAP-T
+    S46Dmr(Ipv6Net),
+    /// 92 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.4>
+    S46V4v6bind(S46V4v6Bind),
+    /// 93 - <https://datatracker.ietf.org/doc/html/rfc7598#section-4.5>
+    S46Portparams(S46PortParams),
+    /// 94 - <https://datatracker.ietf.org/doc/html/rfc7598#section-5.1>
+    S46ContMape(DhcpOptions),
+    /// 95 - <https://datatracker.ietf.org/doc/html/rfc7598#section-5.2>
+    S46ContMapt(DhcpOptions),
+    /// 96 - <https://datatracker.ietf.org/doc/html/rfc7598#section-5.3>
+    S46ContLw(DhcpOptions),
+    /// 114 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FBindingStatus(BindingStatus),
+    /// 115 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FConnectFlags(u16),
+    /// 116 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    /// holds the host name, zone name & DNS flags options
+    Fdnsremovalinfo(DhcpOptions),
+    /// 117 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FDNSHostName(Domain),
+    /// 118 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FDNSZoneName(Domain),
+    /// 119 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    Fdnsflags(u16),
+    /// 120 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    Fexpirationtime(u32),
+    /// 121 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FMaxUnackedBndupd(u32),
+    /// 122 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    /// maximum client lead time
+    FMclt(u32),
+    /// 123 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FPartnerLifetime(u32),
+    /// 124 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FPartnerLifetimeSent(u32),
+    /// 125 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FPartnerDownTime(u32),
+    /// 126 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FPartnerRawCltTime(u32),
+    /// 127 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FProtocolVersion(u32),
+    /// 128 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FKeepaliveTime(u32),
+    /// 129 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FReconfigureData(ReconfigureData),
+    /// 130 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FRelationshipName(String),
+    /// 131 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FServerFlags(u8),
+    /// 132 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FServerState(ServerState),
+    /// 133 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FStartTimeOfState(u32),
+    /// 134 - <https://datatracker.ietf.org/doc/html/rfc8156>
+    FStateExpirationTime(u32),
+    /// 135 - <https://datatracker.ietf.org/doc/html/rfc8357#section-5>
+    /// source port of the downstream relay or client
+    RelayPort(u16),
     /// An unknown or unimplemented option type
     Unknown(UnknownOption),
 }
@@ -175,10 +300,69 @@ impl Ord for DhcpOption {
     }
 }
 
-/// wrapper around interface id
+/// Relay agent remote-id - <https://datatracker.ietf.org/doc/html/rfc4649#section-3>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RemoteId {
+    /// IANA enterprise number of the vendor assigning the id
+    pub enterprise: u32,
+    /// remote-id, opaque & vendor specific
+    pub id: Vec<u8>,
+}
+
+impl Decodable for RemoteId {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(RemoteId {
+            enterprise: decoder.field("enterprise", |d| d.read_u32())?,
+            id: decoder.field("id", |d| {
+                let len = d.buffer().len();
+                Ok(d.read_slice(len)?.to_vec())
+            })?,
+        })
+    }
+}
+
+/// Client link-layer address, added by the first-hop relay -
+/// <https://datatracker.ietf.org/doc/html/rfc6939#section-4>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct InterfaceId {
-    pub id: String,
+pub struct ClientLinklayerAddr {
+    /// hardware type of the client's link. 16 bits, unlike the v4 htype,
+    /// see [`ClientLinklayerAddr::hw_type`]
+    pub htype: u16,
+    /// link-layer address of the client
+    pub addr: Vec<u8>,
+}
+
+impl ClientLinklayerAddr {
+    /// create the option from a typed hardware address
+    pub fn new(addr: &v4::HardwareAddr) -> Self {
+        Self {
+            htype: u8::from(addr.htype()) as u16,
+            addr: addr.as_bytes().to_vec(),
+        }
+    }
+    /// the hardware type as a v4 [`v4::HType`], `None` if it is above 255
+    pub fn hw_type(&self) -> Option<v4::HType> {
+        u8::try_from(self.htype).ok().map(v4::HType::from)
+    }
+    /// the typed hardware address, `None` if the hardware type is above 255
+    /// or the address is not valid for it
+    pub fn hw_addr(&self) -> Option<v4::HardwareAddr> {
+        v4::HardwareAddr::new(self.hw_type()?, &self.addr).ok()
+    }
+}
+
+impl Decodable for ClientLinklayerAddr {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(ClientLinklayerAddr {
+            htype: decoder.field("htype", |d| d.read_u16())?,
+            addr: decoder.field("addr", |d| {
+                let len = d.buffer().len();
+                Ok(d.read_slice(len)?.to_vec())
+            })?,
+        })
+    }
 }
 
 /// vendor options
@@ -221,6 +405,184 @@ fn decode_data(decoder: &'_ mut Decoder<'_>) -> Vec<Vec<u8>> {
     data
 }
 
+/// Client FQDN. Uses the same flags as the v4 option, the E bit is ignored
+///
+/// ```rust
+/// # use dhcproto::{v6::ClientFqdn, v4::fqdn::{DdnsPolicy, FqdnFlags}, Domain, Name};
+/// # use std::str::FromStr;
+/// let client = ClientFqdn::new(FqdnFlags::default().set_s(true), Domain::new(Name::from_str("host").unwrap()));
+/// // server fills in the rest of the name & decides who updates DNS
+/// let reply = client.server_response(
+///     DdnsPolicy::ServerUpdates,
+///     Domain::new(Name::from_str("host.example.com.").unwrap()),
+/// );
+/// assert!(reply.flags.s());
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientFqdn {
+    /// N, O & S flags, the v6 option has no E bit so names are always
+    /// canonical wire format
+    pub flags: FqdnFlags,
+    /// may be a partial name (not fully qualified)
+    pub domain: Domain,
+}
+
+impl ClientFqdn {
+    /// create a new client FQDN
+    pub fn new(flags: FqdnFlags, domain: Domain) -> Self {
+        Self { flags, domain }
+    }
+    /// the option a server should reply with, given its update policy and the
+    /// fully qualified name it assigned
+    pub fn server_response(&self, policy: DdnsPolicy, domain: Domain) -> ClientFqdn {
+        ClientFqdn {
+            flags: self.flags.server_response(policy).set_e(false),
+            domain,
+        }
+    }
+}
+
+/// Leasequery query
+/// <https://datatracker.ietf.org/doc/html/rfc5007>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LqQuery {
+    pub qtype: QueryType,
+    /// link to query on, unspecified for all links
+    pub link_address: Ipv6Addr,
+    // 17 + opts.len()
+    pub opts: DhcpOptions,
+}
+
+impl Decodable for LqQuery {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(LqQuery {
+            qtype: decoder.field("qtype", |d| d.read_u8())?.into(),
+            link_address: decoder.field("link_address", |d| d.read::<16>())?.into(),
+            opts: decoder.field("opts", DhcpOptions::decode)?,
+        })
+    }
+}
+
+/// Leasequery query types
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryType {
+    /// 1 - by IAAddr option
+    Address,
+    /// 2 - by ClientId option
+    ClientId,
+    /// 3 - by RelayId option <https://datatracker.ietf.org/doc/html/rfc5460>
+    RelayId,
+    /// 4 - by link address <https://datatracker.ietf.org/doc/html/rfc5460>
+    LinkAddress,
+    /// 5 - by RemoteId option <https://datatracker.ietf.org/doc/html/rfc5460>
+    RemoteId,
+    /// unknown/unimplemented query type
+    Unknown(u8),
+}
+
+impl From<u8> for QueryType {
+    fn from(n: u8) -> Self {
+        use QueryType::*;
+        match n {
+            1 => Address,
+            2 => ClientId,
+            3 => RelayId,
+            4 => LinkAddress,
+            5 => RemoteId,
+            _ => Unknown(n),
+        }
+    }
+}
+
+impl From<QueryType> for u8 {
+    fn from(qtype: QueryType) -> Self {
+        use QueryType::*;
+        match qtype {
+            Address => 1,
+            ClientId => 2,
+            RelayId => 3,
+            LinkAddress => 4,
+            RemoteId => 5,
+            Unknown(n) => n,
+        }
+    }
+}
+
+/// Leasequery client data, holds the bindings of one client
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ClientData {
+    pub opts: DhcpOptions,
+}
+
+impl ClientData {
+    /// the client's DUID
+    pub fn client_id(&self) -> Option<&[u8]> {
+        match self.opts.get(OptionCode::ClientId)? {
+            DhcpOption::ClientId(duid) => Some(duid),
+            _ => None,
+        }
+    }
+    /// addresses leased to the client
+    pub fn addrs(&self) -> impl Iterator<Item = &IAAddr> {
+        self.opts.iter().filter_map(|opt| match opt {
+            DhcpOption::IAAddr(addr) => Some(addr),
+            _ => None,
+        })
+    }
+    /// prefixes delegated to the client
+    pub fn prefixes(&self) -> impl Iterator<Item = &IAPrefix> {
+        self.opts.iter().filter_map(|opt| match opt {
+            DhcpOption::IAPrefix(prefix) => Some(prefix),
+            _ => None,
+        })
+    }
+    /// seconds since the client last talked to the server
+    pub fn clt_time(&self) -> Option<u32> {
+        match self.opts.get(OptionCode::CltTime)? {
+            DhcpOption::CltTime(secs) => Some(*secs),
+            _ => None,
+        }
+    }
+    /// the relay-forward message last received for the client
+    pub fn relay_data(&self) -> Option<&LqRelayData> {
+        match self.opts.get(OptionCode::LqRelayData)? {
+            DhcpOption::LqRelayData(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl Decodable for ClientData {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(ClientData {
+            opts: decoder.field("opts", DhcpOptions::decode)?,
+        })
+    }
+}
+
+/// Leasequery relay data
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LqRelayData {
+    /// address of the relay agent the message came from
+    pub peer_address: Ipv6Addr,
+    /// the last relay-forward message received for the client
+    pub relay: RelayMessage,
+}
+
+impl Decodable for LqRelayData {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(LqRelayData {
+            peer_address: decoder.field("peer_address", |d| d.read::<16>())?.into(),
+            relay: decoder.field("relay", RelayMessage::decode)?,
+        })
+    }
+}
+
 /// Server Unicast
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -338,13 +700,12 @@ pub struct Authentication {
 
 impl Decodable for Authentication {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
-        let len = decoder.buffer().len();
         Ok(Authentication {
-            proto: decoder.read_u8()?,
-            algo: decoder.read_u8()?,
-            rdm: decoder.read_u8()?,
-            replay_detection: decoder.read_u64()?,
-            info: decoder.read_slice(len - 11)?.to_vec(),
+            proto: decoder.field("proto", |d| d.read_u8())?,
+            algo: decoder.field("algo", |d| d.read_u8())?,
+            rdm: decoder.field("rdm", |d| d.read_u8())?,
+            replay_detection: decoder.field("replay_detection", |d| d.read_u64())?,
+            info: decoder.read_slice(decoder.buffer().len())?.to_vec(),
         })
     }
 }
@@ -388,8 +749,8 @@ pub struct IATA {
 impl Decodable for IATA {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(IATA {
-            id: decoder.read_u32()?,
-            opts: DhcpOptions::decode(decoder)?,
+            id: decoder.field("id", |d| d.read_u32())?,
+            opts: decoder.field("opts", DhcpOptions::decode)?,
         })
     }
 }
@@ -408,10 +769,10 @@ pub struct IANA {
 impl Decodable for IANA {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(IANA {
-            id: decoder.read_u32()?,
-            t1: decoder.read_u32()?,
-            t2: decoder.read_u32()?,
-            opts: DhcpOptions::decode(decoder)?,
+            id: decoder.field("id", |d| d.read_u32())?,
+            t1: decoder.field("t1", |d| d.read_u32())?,
+            t2: decoder.field("t2", |d| d.read_u32())?,
+            opts: decoder.field("opts", DhcpOptions::decode)?,
         })
     }
 }
@@ -427,13 +788,23 @@ pub struct IAPD {
     pub opts: DhcpOptions,
 }
 
+impl IAPD {
+    /// the prefixes delegated in this IA
+    pub fn prefixes(&self) -> impl Iterator<Item = &IAPrefix> {
+        self.opts.iter().filter_map(|opt| match opt {
+            DhcpOption::IAPrefix(prefix) => Some(prefix),
+            _ => None,
+        })
+    }
+}
+
 impl Decodable for IAPD {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(IAPD {
-            id: decoder.read_u32()?,
-            t1: decoder.read_u32()?,
-            t2: decoder.read_u32()?,
-            opts: DhcpOptions::decode(decoder)?,
+            id: decoder.field("id", |d| d.read_u32())?,
+            t1: decoder.field("t1", |d| d.read_u32())?,
+            t2: decoder.field("t2", |d| d.read_u32())?,
+            opts: decoder.field("opts", DhcpOptions::decode)?,
         })
     }
 }
@@ -450,18 +821,176 @@ pub struct IAPrefix {
     pub opts: DhcpOptions,
 }
 
+impl IAPrefix {
+    /// a prefix with no options
+    pub fn new(prefix: Ipv6Net, preferred_lifetime: u32, valid_lifetime: u32) -> Self {
+        Self {
+            preferred_lifetime,
+            valid_lifetime,
+            prefix_len: prefix.prefix_len(),
+            prefix_ip: prefix.network(),
+            opts: DhcpOptions::new(),
+        }
+    }
+    /// the delegated prefix, `None` if `prefix_len` is greater than 128
+    pub fn prefix(&self) -> Option<Ipv6Net> {
+        Ipv6Net::new(self.prefix_ip, self.prefix_len)
+            .ok()
+            .map(|net| net.trunc())
+    }
+    /// the prefix excluded from the delegation by a [`PdExclude`] option,
+    /// usually the one used on the link to the delegating router
+    pub fn excluded(&self) -> Option<Ipv6Net> {
+        match self.opts.get(OptionCode::PdExclude)? {
+            DhcpOption::PdExclude(exclude) => exclude.prefix(self.prefix()?),
+            _ => None,
+        }
+    }
+}
+
 impl Decodable for IAPrefix {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(IAPrefix {
-            preferred_lifetime: decoder.read_u32()?,
-            valid_lifetime: decoder.read_u32()?,
-            prefix_len: decoder.read_u8()?,
-            prefix_ip: decoder.read::<16>()?.into(),
-            opts: DhcpOptions::decode(decoder)?,
+            preferred_lifetime: decoder.field("preferred_lifetime", |d| d.read_u32())?,
+            valid_lifetime: decoder.field("valid_lifetime", |d| d.read_u32())?,
+            prefix_len: decoder.field("prefix_len", |d| d.read_u8())?,
+            prefix_ip: decoder.field("prefix_ip", |d| d.read::<16>())?.into(),
+            opts: decoder.field("opts", DhcpOptions::decode)?,
+        })
+    }
+}
+
+/// Prefix Exclude, a prefix within a delegated prefix that the requesting
+/// router must not use. Only the bits following the delegated prefix are
+/// sent, see [`PdExclude::new`] & [`PdExclude::prefix`]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PdExclude {
+    /// length of the excluded prefix
+    pub prefix_len: u8,
+    /// bits of the excluded prefix after the delegated prefix, left aligned
+    pub subnet_id: Vec<u8>,
+}
+
+impl PdExclude {
+    /// exclude `excluded` from `delegated`. Returns `None` unless `excluded`
+    /// is a longer prefix within `delegated`
+    pub fn new(delegated: Ipv6Net, excluded: Ipv6Net) -> Option<Self> {
+        let (del, ex) = (delegated.prefix_len(), excluded.prefix_len());
+        if ex <= del || !delegated.contains(&excluded) {
+            return None;
+        }
+        let bits = u128::from(excluded.network()) << del;
+        let len = (ex - del + 7) as usize / 8;
+        Some(Self {
+            prefix_len: ex,
+            subnet_id: bits.to_be_bytes()[..len].to_vec(),
+        })
+    }
+    /// the excluded prefix within `delegated`. Returns `None` if the
+    /// option is malformed for `delegated`
+    pub fn prefix(&self, delegated: Ipv6Net) -> Option<Ipv6Net> {
+        let (del, ex) = (delegated.prefix_len(), self.prefix_len);
+        if ex <= del || ex > 128 || self.subnet_id.len() != (ex - del + 7) as usize / 8 {
+            return None;
+        }
+        let mut bytes = [0; 16];
+        bytes[..self.subnet_id.len()].copy_from_slice(&self.subnet_id);
+        let bits = u128::from_be_bytes(bytes) >> del;
+        let addr = Ipv6Addr::from(u128::from(delegated.network()) | bits);
+        Some(Ipv6Net::new(addr, ex).ok()?.trunc())
+    }
+}
+
+impl Decodable for PdExclude {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(PdExclude {
+            prefix_len: decoder.field("prefix_len", |d| d.read_u8())?,
+            subnet_id: decoder.field("subnet_id", |d| {
+                let len = d.buffer().len();
+                Ok(d.read_slice(len)?.to_vec())
+            })?,
         })
     }
 }
 
+/// NTP server sub-option - <https://datatracker.ietf.org/doc/html/rfc5908#section-4>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NtpSuboption {
+    /// 1 - unicast address of an NTP server or peer
+    ServerAddr(Ipv6Addr),
+    /// 2 - multicast address to listen on for NTP
+    MulticastAddr(Ipv6Addr),
+    /// 3 - FQDN of an NTP server or peer
+    ServerFqdn(Domain),
+    /// unknown/unimplemented sub-option
+    Unknown {
+        /// sub-option code
+        code: u16,
+        /// sub-option value
+        data: Vec<u8>,
+    },
+}
+
+impl Decodable for NtpSuboption {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        // address sub-options hold exactly one address
+        fn read_addr(d: &mut Decoder<'_>, len: usize) -> DecodeResult<Ipv6Addr> {
+            if len != 16 {
+                return Err(DecodeError::Unknown(
+                    format!("invalid NTP address length {len}").into(),
+                ));
+            }
+            Ok(d.read::<16>()?.into())
+        }
+        let code = decoder.read_u16()?;
+        let len = decoder.read_u16()? as usize;
+        let mut decoder = decoder.sub_decoder(len)?;
+        Ok(match code {
+            1 => NtpSuboption::ServerAddr(decoder.field("addr", |d| read_addr(d, len))?),
+            2 => NtpSuboption::MulticastAddr(decoder.field("addr", |d| read_addr(d, len))?),
+            3 => NtpSuboption::ServerFqdn(
+                decoder.field("fqdn", |d| Domain::decode_partial(d.read_slice(len)?))?,
+            ),
+            _ => NtpSuboption::Unknown {
+                code,
+                data: decoder.read_slice(len)?.to_vec(),
+            },
+        })
+    }
+}
+
+impl Encodable for NtpSuboption {
+    fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
+        match self {
+            NtpSuboption::ServerAddr(addr) => {
+                e.write_u16(1)?;
+                e.write_u16(16)?;
+                e.write_u128((*addr).into())?;
+            }
+            NtpSuboption::MulticastAddr(addr) => {
+                e.write_u16(2)?;
+                e.write_u16(16)?;
+                e.write_u128((*addr).into())?;
+            }
+            NtpSuboption::ServerFqdn(name) => {
+                // uncompressed: https://datatracker.ietf.org/doc/html/rfc5908#section-4.3
+                let buf = Domain::encode_list(std::slice::from_ref(name), false)?;
+                e.write_u16(3)?;
+                e.write_u16(buf.len() as u16)?;
+                e.write_slice(&buf)?;
+            }
+            NtpSuboption::Unknown { code, data } => {
+                e.write_u16(*code)?;
+                e.write_u16(data.len() as u16)?;
+                e.write_slice(data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Identity Association Address
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -478,10 +1007,10 @@ pub struct IAAddr {
 impl Decodable for IAAddr {
     fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
         Ok(IAAddr {
-            addr: decoder.read::<16>()?.into(),
-            preferred_life: decoder.read_u32()?,
-            valid_life: decoder.read_u32()?,
-            opts: DhcpOptions::decode(decoder)?,
+            addr: decoder.field("addr", |d| d.read::<16>())?.into(),
+            preferred_life: decoder.field("preferred_life", |d| d.read_u32())?,
+            valid_life: decoder.field("valid_life", |d| d.read_u32())?,
+            opts: decoder.field("opts", DhcpOptions::decode)?,
         })
     }
 }
@@ -518,11 +1047,12 @@ impl UnknownOption {
 impl Decodable for DhcpOptions {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         let mut opts = Vec::new();
-        while let Ok(opt) = DhcpOption::decode(decoder) {
-            opts.push(opt);
+        // options are length delimited, anything left over is malformed
+        while !decoder.buffer().is_empty() {
+            opts.push(DhcpOption::decode(decoder)?);
         }
         // sorts by OptionCode
-        opts.sort_unstable();
+        opts.sort();
         Ok(DhcpOptions(opts))
     }
 }
@@ -537,103 +1067,183 @@ impl Decodable for DhcpOption {
     fn decode(decoder: &mut Decoder<'_>) -> DecodeResult<Self> {
         let code = decoder.read_u16()?.into();
         let len = decoder.read_u16()? as usize;
-        Ok(match code {
-            OptionCode::ClientId => DhcpOption::ClientId(decoder.read_slice(len)?.to_vec()),
-            OptionCode::ServerId => DhcpOption::ServerId(decoder.read_slice(len)?.to_vec()),
-            OptionCode::IANA => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IANA(IANA::decode(&mut dec)?)
-            }
-            OptionCode::IATA => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IATA(IATA::decode(&mut dec)?)
-            }
-            OptionCode::IAAddr => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IAAddr(IAAddr::decode(&mut dec)?)
-            }
-            OptionCode::ORO => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::ORO(ORO::decode(&mut dec)?)
-            }
-            OptionCode::Preference => DhcpOption::Preference(decoder.read_u8()?),
-            OptionCode::ElapsedTime => DhcpOption::ElapsedTime(decoder.read_u16()?),
-            OptionCode::RelayMsg => {
-                let mut relay_dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::RelayMsg(RelayMessage::decode(&mut relay_dec)?)
-            }
-            OptionCode::Authentication => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::Authentication(Authentication::decode(&mut dec)?)
-            }
-            OptionCode::ServerUnicast => DhcpOption::ServerUnicast(decoder.read::<16>()?.into()),
-            OptionCode::StatusCode => DhcpOption::StatusCode(StatusCode {
-                status: decoder.read_u16()?.into(),
-                msg: decoder.read_string(len - 1)?,
-            }),
-            OptionCode::RapidCommit => DhcpOption::RapidCommit,
-            OptionCode::UserClass => {
-                let buf = decoder.read_slice(len)?;
-                DhcpOption::UserClass(UserClass {
-                    data: decode_data(&mut Decoder::new(buf)),
-                })
-            }
-            OptionCode::VendorClass => {
-                let num = decoder.read_u32()?;
-                let buf = decoder.read_slice(len - 4)?;
-                DhcpOption::VendorClass(VendorClass {
-                    num,
-                    data: decode_data(&mut Decoder::new(buf)),
-                })
-            }
-            OptionCode::VendorOpts => DhcpOption::VendorOpts(VendorOpts {
-                num: decoder.read_u32()?,
-                opts: {
-                    let mut opt_decoder = Decoder::new(decoder.read_slice(len - 4)?);
-                    DhcpOptions::decode(&mut opt_decoder)?
-                },
-            }),
-            OptionCode::InterfaceId => DhcpOption::InterfaceId(decoder.read_slice(len)?.to_vec()),
-            OptionCode::ReconfMsg => DhcpOption::ReconfMsg(decoder.read_u8()?.into()),
-            OptionCode::ReconfAccept => DhcpOption::ReconfAccept,
-            OptionCode::DomainNameServers => {
-                DhcpOption::DomainNameServers(decoder.read_ipv6s(len)?)
-            }
-            OptionCode::IAPD => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IAPD(IAPD::decode(&mut dec)?)
-            }
-            OptionCode::IAPrefix => {
-                let mut dec = Decoder::new(decoder.read_slice(len)?);
-                DhcpOption::IAPrefix(IAPrefix::decode(&mut dec)?)
-            }
-            OptionCode::DomainSearchList => {
-                let mut name_decoder = BinDecoder::new(decoder.read_slice(len)?);
-                let mut names = Vec::new();
-                while let Ok(name) = Name::read(&mut name_decoder) {
-                    names.push(Domain(name));
-                }
+        // decode the option body from its own decoder so a bad option can't
+        // consume bytes from the one that follows it
+        let mut dec = decoder.sub_decoder(len)?;
+        decode_inner(code, len, &mut dec)
+            .map_err(|err| err.with_context(dec.offset(), PathSegment::Opt(format!("{code:?}"))))
+    }
+}
 
-                DhcpOption::DomainSearchList(names)
+#[inline]
+fn decode_inner(
+    code: OptionCode,
+    len: usize,
+    decoder: &mut Decoder<'_>,
+) -> DecodeResult<DhcpOption> {
+    Ok(match code {
+        OptionCode::ClientId => DhcpOption::ClientId(decoder.read_slice(len)?.to_vec()),
+        OptionCode::ServerId => DhcpOption::ServerId(decoder.read_slice(len)?.to_vec()),
+        OptionCode::IANA => DhcpOption::IANA(IANA::decode(decoder)?),
+        OptionCode::IATA => DhcpOption::IATA(IATA::decode(decoder)?),
+        OptionCode::IAAddr => DhcpOption::IAAddr(IAAddr::decode(decoder)?),
+        OptionCode::ORO => DhcpOption::ORO(ORO::decode(decoder)?),
+        OptionCode::Preference => DhcpOption::Preference(decoder.read_u8()?),
+        OptionCode::ElapsedTime => DhcpOption::ElapsedTime(decoder.read_u16()?),
+        OptionCode::RelayMsg => DhcpOption::RelayMsg(RelayMessage::decode(decoder)?),
+        OptionCode::Authentication => DhcpOption::Authentication(Authentication::decode(decoder)?),
+        OptionCode::ServerUnicast => DhcpOption::ServerUnicast(decoder.read::<16>()?.into()),
+        OptionCode::StatusCode => DhcpOption::StatusCode(StatusCode {
+            status: decoder.field("status", |d| d.read_u16())?.into(),
+            // status is included in length, msg is the remainder
+            msg: decoder.field("msg", |d| d.read_string(d.buffer().len()))?,
+        }),
+        OptionCode::RapidCommit => DhcpOption::RapidCommit,
+        OptionCode::UserClass => DhcpOption::UserClass(UserClass {
+            data: decode_data(decoder),
+        }),
+        OptionCode::VendorClass => DhcpOption::VendorClass(VendorClass {
+            num: decoder.field("num", |d| d.read_u32())?,
+            data: decode_data(decoder),
+        }),
+        OptionCode::VendorOpts => DhcpOption::VendorOpts(VendorOpts {
+            num: decoder.field("num", |d| d.read_u32())?,
+            opts: decoder.field("opts", DhcpOptions::decode)?,
+        }),
+        OptionCode::InterfaceId => DhcpOption::InterfaceId(decoder.read_slice(len)?.to_vec()),
+        OptionCode::ReconfMsg => DhcpOption::ReconfMsg(decoder.read_u8()?.into()),
+        OptionCode::ReconfAccept => DhcpOption::ReconfAccept,
+        OptionCode::SipServerD => {
+            DhcpOption::SipServerD(Domain::decode_list(decoder.read_slice(len)?))
+        }
+        OptionCode::SipServerA => DhcpOption::SipServerA(decoder.read_ipv6s(len)?),
+        OptionCode::DomainNameServers => DhcpOption::DomainNameServers(decoder.read_ipv6s(len)?),
+        OptionCode::SntpServers => DhcpOption::SntpServers(decoder.read_ipv6s(len)?),
+        OptionCode::InformationRefreshTime => {
+            DhcpOption::InformationRefreshTime(decoder.read_u32()?)
+        }
+        OptionCode::RemoteId => DhcpOption::RemoteId(RemoteId::decode(decoder)?),
+        OptionCode::SubscriberId => DhcpOption::SubscriberId(decoder.read_slice(len)?.to_vec()),
+        OptionCode::ERO => {
+            if len % 2 != 0 {
+                return Err(DecodeError::NotEnoughBytes);
             }
-            // not yet implemented
-            OptionCode::Unknown(code) => DhcpOption::Unknown(UnknownOption {
-                code,
-                data: decoder.read_slice(len)?.to_vec(),
-            }),
-            _ => DhcpOption::Unknown(UnknownOption {
-                code: code.into(),
-                data: decoder.read_slice(len)?.to_vec(),
-            }),
-        })
-    }
+            let mut codes = Vec::with_capacity(len / 2);
+            while !decoder.buffer().is_empty() {
+                codes.push(decoder.read_u16()?.into());
+            }
+            DhcpOption::ERO(codes)
+        }
+        OptionCode::IAPD => DhcpOption::IAPD(IAPD::decode(decoder)?),
+        OptionCode::IAPrefix => DhcpOption::IAPrefix(IAPrefix::decode(decoder)?),
+        OptionCode::ClientFqdn => DhcpOption::ClientFqdn(ClientFqdn {
+            flags: FqdnFlags::from_v6(decoder.field("flags", |d| d.read_u8())?),
+            domain: decoder.field("domain", |d| Domain::decode_partial(d.read_slice(len - 1)?))?,
+        }),
+        OptionCode::DomainSearchList => {
+            DhcpOption::DomainSearchList(Domain::decode_list(decoder.read_slice(len)?))
+        }
+        OptionCode::LqQuery => DhcpOption::LqQuery(LqQuery::decode(decoder)?),
+        OptionCode::ClientData => DhcpOption::ClientData(ClientData::decode(decoder)?),
+        OptionCode::CltTime => DhcpOption::CltTime(decoder.read_u32()?),
+        OptionCode::LqRelayData => DhcpOption::LqRelayData(LqRelayData::decode(decoder)?),
+        OptionCode::LqClientLink => DhcpOption::LqClientLink(decoder.read_ipv6s(len)?),
+        OptionCode::RelayId => DhcpOption::RelayId(decoder.read_slice(len)?.to_vec()),
+        OptionCode::NtpServer => {
+            let mut subopts = Vec::new();
+            while !decoder.buffer().is_empty() {
+                subopts.push(NtpSuboption::decode(decoder)?);
+            }
+            DhcpOption::NtpServer(subopts)
+        }
+        OptionCode::OptBootfileUrl => DhcpOption::OptBootfileUrl(decoder.read_str(len)?.parse()?),
+        OptionCode::OptBootfileParam => {
+            let mut params = Vec::new();
+            while !decoder.buffer().is_empty() {
+                let len = decoder.read_u16()? as usize;
+                params.push(decoder.read_string(len)?);
+            }
+            DhcpOption::OptBootfileParam(params)
+        }
+        OptionCode::ClientArchType => {
+            let mut archs = Vec::new();
+            while !decoder.buffer().is_empty() {
+                archs.push(decoder.read_u16()?.into());
+            }
+            DhcpOption::ClientArchType(archs)
+        }
+        OptionCode::Nii => {
+            DhcpOption::Nii(decoder.read_u8()?, decoder.read_u8()?, decoder.read_u8()?)
+        }
+        OptionCode::AftrName => DhcpOption::AftrName(
+            decoder.field("aftr_name", |d| Domain::decode_partial(d.read_slice(len)?))?,
+        ),
+        OptionCode::Rsoo => DhcpOption::Rsoo(DhcpOptions::decode(decoder)?),
+        OptionCode::PdExclude => DhcpOption::PdExclude(PdExclude::decode(decoder)?),
+        OptionCode::ClientLinklayerAddr => {
+            DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr::decode(decoder)?)
+        }
+        OptionCode::LinkAddress => DhcpOption::LinkAddress(decoder.read::<16>()?.into()),
+        OptionCode::SolMaxRt => DhcpOption::SolMaxRt(decoder.read_u32()?),
+        OptionCode::InfMaxRt => DhcpOption::InfMaxRt(decoder.read_u32()?),
+        OptionCode::Dhcpv4Msg => DhcpOption::Dhcpv4Msg(Box::new(v4::Message::decode(decoder)?)),
+        OptionCode::Dhcp4ODhcp6Server => DhcpOption::Dhcp4ODhcp6Server(decoder.read_ipv6s(len)?),
+        OptionCode::S46Rule => DhcpOption::S46Rule(S46Rule::decode(decoder)?),
+        OptionCode::S46Br => DhcpOption::S46Br(decoder.read::<16>()?.into()),
+        OptionCode::S46Dmr => DhcpOption::S46Dmr(s46::read_prefix6(decoder)?),
+        OptionCode::S46V4v6bind => DhcpOption::S46V4v6bind(S46V4v6Bind::decode(decoder)?),
+        OptionCode::S46Portparams => DhcpOption::S46Portparams(S46PortParams::decode(decoder)?),
+        OptionCode::S46ContMape => DhcpOption::S46ContMape(DhcpOptions::decode(decoder)?),
+        OptionCode::S46ContMapt => DhcpOption::S46ContMapt(DhcpOptions::decode(decoder)?),
+        OptionCode::S46ContLw => DhcpOption::S46ContLw(DhcpOptions::decode(decoder)?),
+        OptionCode::FBindingStatus => DhcpOption::FBindingStatus(decoder.read_u8()?.into()),
+        OptionCode::FConnectFlags => DhcpOption::FConnectFlags(decoder.read_u16()?),
+        OptionCode::Fdnsremovalinfo => DhcpOption::Fdnsremovalinfo(DhcpOptions::decode(decoder)?),
+        OptionCode::FDNSHostName => DhcpOption::FDNSHostName(
+            decoder.field("host_name", |d| Domain::decode_partial(d.read_slice(len)?))?,
+        ),
+        OptionCode::FDNSZoneName => DhcpOption::FDNSZoneName(
+            decoder.field("zone_name", |d| Domain::decode_partial(d.read_slice(len)?))?,
+        ),
+        OptionCode::Fdnsflags => DhcpOption::Fdnsflags(decoder.read_u16()?),
+        OptionCode::Fexpirationtime => DhcpOption::Fexpirationtime(decoder.read_u32()?),
+        OptionCode::FMaxUnackedBndupd => DhcpOption::FMaxUnackedBndupd(decoder.read_u32()?),
+        OptionCode::FMclt => DhcpOption::FMclt(decoder.read_u32()?),
+        OptionCode::FPartnerLifetime => DhcpOption::FPartnerLifetime(decoder.read_u32()?),
+        OptionCode::FPartnerLifetimeSent => DhcpOption::FPartnerLifetimeSent(decoder.read_u32()?),
+        OptionCode::FPartnerDownTime => DhcpOption::FPartnerDownTime(decoder.read_u32()?),
+        OptionCode::FPartnerRawCltTime => DhcpOption::FPartnerRawCltTime(decoder.read_u32()?),
+        OptionCode::FProtocolVersion => DhcpOption::FProtocolVersion(decoder.read_u32()?),
+        OptionCode::FKeepaliveTime => DhcpOption::FKeepaliveTime(decoder.read_u32()?),
+        OptionCode::FReconfigureData => {
+            DhcpOption::FReconfigureData(ReconfigureData::decode(decoder)?)
+        }
+        OptionCode::FRelationshipName => DhcpOption::FRelationshipName(decoder.read_string(len)?),
+        OptionCode::FServerFlags => DhcpOption::FServerFlags(decoder.read_u8()?),
+        OptionCode::FServerState => DhcpOption::FServerState(decoder.read_u8()?.into()),
+        OptionCode::FStartTimeOfState => DhcpOption::FStartTimeOfState(decoder.read_u32()?),
+        OptionCode::FStateExpirationTime => DhcpOption::FStateExpirationTime(decoder.read_u32()?),
+        OptionCode::RelayPort => DhcpOption::RelayPort(decoder.read_u16()?),
+        // not yet implemented
+        OptionCode::Unknown(code) => DhcpOption::Unknown(UnknownOption {
+            code,
+            data: decoder.read_slice(len)?.to_vec(),
+        }),
+        _ => DhcpOption::Unknown(UnknownOption {
+            code: code.into(),
+            data: decoder.read_slice(len)?.to_vec(),
+        }),
+    })
 }
 impl Encodable for DhcpOption {
     fn encode(&self, e: &'_ mut Encoder<'_>) -> EncodeResult<()> {
         let code: OptionCode = self.into();
         e.write_u16(code.into())?;
         match self {
-            DhcpOption::ClientId(duid) | DhcpOption::ServerId(duid) => {
+            DhcpOption::ClientId(duid)
+            | DhcpOption::ServerId(duid)
+            | DhcpOption::RelayId(duid)
+            | DhcpOption::SubscriberId(duid) => {
                 e.write_u16(duid.len() as u16)?;
                 e.write_slice(duid)?;
             }
@@ -730,6 +1340,59 @@ impl Encodable for DhcpOption {
             DhcpOption::RapidCommit => {
                 e.write_u16(0)?;
             }
+            DhcpOption::RemoteId(RemoteId { enterprise, id }) => {
+                e.write_u16(4 + id.len() as u16)?;
+                e.write_u32(*enterprise)?;
+                e.write_slice(id)?;
+            }
+            DhcpOption::ERO(codes) => {
+                e.write_u16(codes.len() as u16 * 2)?;
+                for code in codes {
+                    e.write_u16((*code).into())?;
+                }
+            }
+            DhcpOption::Rsoo(opts) | DhcpOption::Fdnsremovalinfo(opts) => {
+                let mut buf = Vec::new();
+                let mut opt_enc = Encoder::new(&mut buf);
+                opts.encode(&mut opt_enc)?;
+                e.write_u16(buf.len() as u16)?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::ClientLinklayerAddr(ClientLinklayerAddr { htype, addr }) => {
+                e.write_u16(2 + addr.len() as u16)?;
+                e.write_u16(*htype)?;
+                e.write_slice(addr)?;
+            }
+            DhcpOption::RelayPort(n) | DhcpOption::FConnectFlags(n) | DhcpOption::Fdnsflags(n) => {
+                e.write_u16(2)?;
+                e.write_u16(*n)?;
+            }
+            DhcpOption::FBindingStatus(status) => {
+                e.write_u16(1)?;
+                e.write_u8((*status).into())?;
+            }
+            DhcpOption::FServerState(state) => {
+                e.write_u16(1)?;
+                e.write_u8((*state).into())?;
+            }
+            DhcpOption::FServerFlags(flags) => {
+                e.write_u16(1)?;
+                e.write_u8(*flags)?;
+            }
+            DhcpOption::FDNSHostName(name) | DhcpOption::FDNSZoneName(name) => {
+                let buf = Domain::encode_list(std::slice::from_ref(name), false)?;
+                e.write_u16(buf.len() as u16)?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::FReconfigureData(ReconfigureData { time, key }) => {
+                e.write_u16(4 + key.len() as u16)?;
+                e.write_u32(*time)?;
+                e.write_slice(key)?;
+            }
+            DhcpOption::FRelationshipName(name) => {
+                e.write_u16(name.len() as u16)?;
+                e.write_slice(name.as_bytes())?;
+            }
             DhcpOption::UserClass(UserClass { data }) => {
                 e.write_u16(data.len() as u16)?;
                 for s in data {
@@ -765,18 +1428,26 @@ impl Encodable for DhcpOption {
             DhcpOption::ReconfAccept => {
                 e.write_u16(0)?;
             }
-            DhcpOption::DomainNameServers(addrs) => {
+            DhcpOption::DomainNameServers(addrs)
+            | DhcpOption::SntpServers(addrs)
+            | DhcpOption::SipServerA(addrs)
+            | DhcpOption::LqClientLink(addrs)
+            | DhcpOption::Dhcp4ODhcp6Server(addrs) => {
                 e.write_u16(addrs.len() as u16 * 16)?;
                 for addr in addrs {
                     e.write_u128((*addr).into())?;
                 }
             }
-            DhcpOption::DomainSearchList(names) => {
-                let mut buf = Vec::new();
-                let mut name_encoder = BinEncoder::new(&mut buf);
-                for name in names {
-                    name.0.emit(&mut name_encoder)?;
-                }
+            DhcpOption::ClientFqdn(ClientFqdn { flags, domain }) => {
+                let buf = domain.encode_partial()?;
+                e.write_u16(1 + buf.len() as u16)?;
+                e.write_u8(flags.to_v6())?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::DomainSearchList(names) | DhcpOption::SipServerD(names) => {
+                // names must not be compressed in v6, though we will still
+                // decode them: https://datatracker.ietf.org/doc/html/rfc8415#section-10
+                let buf = Domain::encode_list(names, false)?;
                 e.write_u16(buf.len() as u16)?;
                 e.write_slice(&buf)?;
             }
@@ -799,10 +1470,168 @@ impl Encodable for DhcpOption {
                 e.write_u128((*prefix_ip).into())?;
                 e.write_slice(&buf)?;
             }
-            DhcpOption::InformationRefreshTime(time) => {
+            DhcpOption::InformationRefreshTime(time)
+            | DhcpOption::CltTime(time)
+            | DhcpOption::SolMaxRt(time)
+            | DhcpOption::InfMaxRt(time)
+            | DhcpOption::Fexpirationtime(time)
+            | DhcpOption::FMaxUnackedBndupd(time)
+            | DhcpOption::FMclt(time)
+            | DhcpOption::FPartnerLifetime(time)
+            | DhcpOption::FPartnerLifetimeSent(time)
+            | DhcpOption::FPartnerDownTime(time)
+            | DhcpOption::FPartnerRawCltTime(time)
+            | DhcpOption::FProtocolVersion(time)
+            | DhcpOption::FKeepaliveTime(time)
+            | DhcpOption::FStartTimeOfState(time)
+            | DhcpOption::FStateExpirationTime(time) => {
                 e.write_u16(4)?;
                 e.write_u32(*time)?;
             }
+            DhcpOption::LqQuery(LqQuery {
+                qtype,
+                link_address,
+                opts,
+            }) => {
+                let mut buf = Vec::new();
+                let mut opt_enc = Encoder::new(&mut buf);
+                opts.encode(&mut opt_enc)?;
+                // buf now has total len
+                e.write_u16(17 + buf.len() as u16)?;
+                e.write_u8((*qtype).into())?;
+                e.write_u128((*link_address).into())?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::ClientData(ClientData { opts }) => {
+                let mut buf = Vec::new();
+                let mut opt_enc = Encoder::new(&mut buf);
+                opts.encode(&mut opt_enc)?;
+                e.write_u16(buf.len() as u16)?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::LqRelayData(LqRelayData {
+                peer_address,
+                relay,
+            }) => {
+                let mut buf = Vec::new();
+                let mut relay_enc = Encoder::new(&mut buf);
+                relay.encode(&mut relay_enc)?;
+                e.write_u16(16 + buf.len() as u16)?;
+                e.write_u128((*peer_address).into())?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::NtpServer(subopts) => {
+                let mut buf = Vec::new();
+                let mut opt_enc = Encoder::new(&mut buf);
+                subopts
+                    .iter()
+                    .try_for_each(|opt| opt.encode(&mut opt_enc))?;
+                e.write_u16(buf.len() as u16)?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::OptBootfileUrl(url) => {
+                let url = url.as_str();
+                e.write_u16(url.len() as u16)?;
+                e.write_slice(url.as_bytes())?;
+            }
+            DhcpOption::OptBootfileParam(params) => {
+                e.write_u16(params.iter().map(|param| 2 + param.len() as u16).sum())?;
+                for param in params {
+                    e.write_u16(param.len() as u16)?;
+                    e.write_slice(param.as_bytes())?;
+                }
+            }
+            DhcpOption::ClientArchType(archs) => {
+                e.write_u16(archs.len() as u16 * 2)?;
+                for arch in archs {
+                    e.write_u16((*arch).into())?;
+                }
+            }
+            DhcpOption::Nii(ty, major, minor) => {
+                e.write_u16(3)?;
+                e.write_u8(*ty)?;
+                e.write_u8(*major)?;
+                e.write_u8(*minor)?;
+            }
+            DhcpOption::AftrName(name) => {
+                // uncompressed & fully qualified: https://datatracker.ietf.org/doc/html/rfc6334#section-3
+                let buf = Domain::encode_list(std::slice::from_ref(name), false)?;
+                e.write_u16(buf.len() as u16)?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::PdExclude(PdExclude {
+                prefix_len,
+                subnet_id,
+            }) => {
+                e.write_u16(1 + subnet_id.len() as u16)?;
+                e.write_u8(*prefix_len)?;
+                e.write_slice(subnet_id)?;
+            }
+            DhcpOption::Dhcpv4Msg(msg) => {
+                let mut buf = Vec::new();
+                let mut msg_enc = Encoder::new(&mut buf);
+                msg.encode(&mut msg_enc)?;
+                e.write_u16(buf.len() as u16)?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::LinkAddress(addr) | DhcpOption::S46Br(addr) => {
+                e.write_u16(16)?;
+                e.write_u128((*addr).into())?;
+            }
+            DhcpOption::S46Rule(S46Rule {
+                fmr,
+                ea_len,
+                ipv4_prefix,
+                ipv6_prefix,
+                opts,
+            }) => {
+                let mut buf = Vec::new();
+                let mut opt_enc = Encoder::new(&mut buf);
+                opts.encode(&mut opt_enc)?;
+                e.write_u16(7 + s46::prefix6_len(ipv6_prefix) + buf.len() as u16)?;
+                e.write_u8(*fmr as u8)?;
+                e.write_u8(*ea_len)?;
+                e.write_u8(ipv4_prefix.prefix_len())?;
+                e.write_u32(ipv4_prefix.network().into())?;
+                s46::write_prefix6(e, ipv6_prefix)?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::S46Dmr(prefix) => {
+                e.write_u16(s46::prefix6_len(prefix))?;
+                s46::write_prefix6(e, prefix)?;
+            }
+            DhcpOption::S46V4v6bind(S46V4v6Bind {
+                ipv4_addr,
+                bind_prefix,
+                opts,
+            }) => {
+                let mut buf = Vec::new();
+                let mut opt_enc = Encoder::new(&mut buf);
+                opts.encode(&mut opt_enc)?;
+                e.write_u16(4 + s46::prefix6_len(bind_prefix) + buf.len() as u16)?;
+                e.write_u32((*ipv4_addr).into())?;
+                s46::write_prefix6(e, bind_prefix)?;
+                e.write_slice(&buf)?;
+            }
+            DhcpOption::S46Portparams(S46PortParams {
+                offset,
+                psid_len,
+                psid_field,
+            }) => {
+                e.write_u16(4)?;
+                e.write_u8(*offset)?;
+                e.write_u8(*psid_len)?;
+                e.write_u16(*psid_field)?;
+            }
+            DhcpOption::S46ContMape(opts)
+            | DhcpOption::S46ContMapt(opts)
+            | DhcpOption::S46ContLw(opts) => {
+                let mut buf = Vec::new();
+                let mut opt_enc = Encoder::new(&mut buf);
+                opts.encode(&mut opt_enc)?;
+                e.write_u16(buf.len() as u16)?;
+                e.write_slice(&buf)?;
+            }
             DhcpOption::Unknown(UnknownOption { data, .. }) => {
                 e.write_u16(data.len() as u16)?;
                 e.write_slice(data)?;
@@ -818,6 +1647,9 @@ where
     T: Ord,
     F: Fn(&T) -> Ordering,
 {
+    if arr.is_empty() {
+        return None;
+    }
     let mut l = 0;
     let mut r = arr.len() - 1;
     while l <= r {
@@ -833,6 +1665,8 @@ where
             return Some(mid);
         } else if mid_cmp == Ordering::Less {
             l = mid + 1;
+        } else if mid == 0 {
+            break;
         } else {
             r = mid - 1;
         }
@@ -847,13 +1681,16 @@ where
     F: Fn(&T) -> Ordering,
 {
     let n = arr.len();
+    if n == 0 {
+        return None;
+    }
     let mut l = 0;
     let mut r = n - 1;
     while l <= r {
         let mid = (l + r) >> 1;
         // SAFETY: we know it is within the length
         let mid_cmp = f(unsafe { arr.get_unchecked(mid) });
-        let nxt_cmp = if mid < n {
+        let nxt_cmp = if mid + 1 < n {
             f(unsafe { arr.get_unchecked(mid + 1) }) == Ordering::Greater
         } else {
             false
@@ -861,6 +1698,9 @@ where
         if (mid == n - 1 || nxt_cmp) && mid_cmp == Ordering::Equal {
             return Some(mid);
         } else if mid_cmp == Ordering::Greater {
+            if mid == 0 {
+                break;
+            }
             r = mid - 1;
         } else {
             l = mid + 1;
@@ -883,6 +1723,501 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::str::FromStr;
+    use trust_dns_proto::rr::Name;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn test_opt(opt: DhcpOption, actual: Vec<u8>) -> Result<()> {
+        let mut out = vec![];
+        let mut enc = Encoder::new(&mut out);
+        opt.encode(&mut enc)?;
+        assert_eq!(out, actual);
+
+        let buf = DhcpOption::decode(&mut Decoder::new(&out))?;
+        assert_eq!(buf, opt);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sip_servers() -> Result<()> {
+        test_opt(
+            DhcpOption::SipServerD(vec![Domain(Name::from_str("sip.example.com.")?)]),
+            vec![
+                0, 21, 0, 17, 3, b's', b'i', b'p', 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 3,
+                b'c', b'o', b'm', 0,
+            ],
+        )?;
+        test_opt(
+            DhcpOption::SipServerA(vec!["2001:db8::1".parse()?]),
+            vec![
+                0, 22, 0, 16, 0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+            ],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_domain_search_list() -> Result<()> {
+        // v6 names are never compressed
+        test_opt(
+            DhcpOption::DomainSearchList(vec![
+                Domain(Name::from_str("a.com.")?),
+                Domain(Name::from_str("b.a.com.")?),
+            ]),
+            vec![
+                0, 24, 0, 16, 1, b'a', 3, b'c', b'o', b'm', 0, 1, b'b', 1, b'a', 3, b'c', b'o',
+                b'm', 0,
+            ],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_fqdn() -> Result<()> {
+        test_opt(
+            DhcpOption::ClientFqdn(ClientFqdn::new(
+                FqdnFlags::default().set_s(true),
+                Domain(Name::from_str("a.com.")?),
+            )),
+            vec![0, 39, 0, 8, 0x01, 1, b'a', 3, b'c', b'o', b'm', 0],
+        )?;
+        // partial name
+        test_opt(
+            DhcpOption::ClientFqdn(ClientFqdn::new(
+                FqdnFlags::default().set_n(true),
+                Domain(Name::from_str("host")?),
+            )),
+            vec![0, 39, 0, 6, 0x04, 4, b'h', b'o', b's', b't'],
+        )?;
+        // empty name
+        test_opt(
+            DhcpOption::ClientFqdn(ClientFqdn::new(FqdnFlags::default(), Domain(Name::new()))),
+            vec![0, 39, 0, 1, 0],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_leasequery_opts() -> Result<()> {
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::IAAddr(IAAddr {
+            addr: "2001:db8::1".parse()?,
+            preferred_life: 0,
+            valid_life: 0,
+            opts: DhcpOptions::new(),
+        }));
+        let query = DhcpOption::LqQuery(LqQuery {
+            qtype: QueryType::Address,
+            link_address: Ipv6Addr::UNSPECIFIED,
+            opts,
+        });
+        let mut bytes = vec![0, 44, 0, 45, 1];
+        bytes.extend([0; 16]);
+        bytes.extend([0, 5, 0, 24, 0x20, 0x01, 0x0d, 0xb8]);
+        bytes.extend([0; 11]);
+        bytes.push(1);
+        bytes.extend([0; 8]);
+        test_opt(query, bytes)?;
+
+        test_opt(DhcpOption::CltTime(60), vec![0, 46, 0, 4, 0, 0, 0, 60])?;
+        test_opt(
+            DhcpOption::RelayId(vec![0, 3, 0, 1]),
+            vec![0, 53, 0, 4, 0, 3, 0, 1],
+        )?;
+        let mut bytes = vec![0, 80, 0, 16, 0x20, 0x01, 0x0d, 0xb8];
+        bytes.extend([0; 11]);
+        bytes.push(1);
+        test_opt(DhcpOption::LinkAddress("2001:db8::1".parse()?), bytes)?;
+
+        // nested client data, with the relay message the server last saw
+        let relay = RelayMessage::decode(&mut Decoder::new(&[
+            12, 0, 0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0xfe, 0x80, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 18, 0, 2, 1, 2,
+        ]))?;
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::ClientId(vec![0, 3, 0, 1, 1, 2, 3, 4, 5, 6]));
+        opts.insert(DhcpOption::CltTime(30));
+        opts.insert(DhcpOption::LqRelayData(LqRelayData {
+            peer_address: "2001:db8::1".parse()?,
+            relay,
+        }));
+        opts.insert(DhcpOption::LqClientLink(vec!["2001:db8::1".parse()?]));
+        let data = ClientData { opts };
+        let opt = DhcpOption::ClientData(data.clone());
+        let mut buf = Vec::new();
+        opt.encode(&mut Encoder::new(&mut buf))?;
+        assert_eq!(DhcpOption::decode(&mut Decoder::new(&buf))?, opt);
+        assert_eq!(data.client_id(), Some(&[0, 3, 0, 1, 1, 2, 3, 4, 5, 6][..]));
+        assert_eq!(data.clt_time(), Some(30));
+        assert_eq!(data.relay_data().unwrap().relay.opts().iter().count(), 1);
+        assert_eq!(data.addrs().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pd_exclude() -> Result<()> {
+        // RFC 6603 example, 2001:db8:0:100::/56 excluded from 2001:db8::/48
+        let delegated: Ipv6Net = "2001:db8::/48".parse()?;
+        let excluded: Ipv6Net = "2001:db8:0:100::/56".parse()?;
+        let exclude = PdExclude::new(delegated, excluded).unwrap();
+        assert_eq!(exclude.subnet_id, vec![0x01]);
+        test_opt(
+            DhcpOption::PdExclude(exclude.clone()),
+            vec![0, 67, 0, 2, 56, 1],
+        )?;
+        assert_eq!(exclude.prefix(delegated), Some(excluded));
+
+        // not byte aligned, /59 from a /56
+        let delegated: Ipv6Net = "2001:db8:0:ab00::/56".parse()?;
+        let excluded: Ipv6Net = "2001:db8:0:abe0::/59".parse()?;
+        let exclude = PdExclude::new(delegated, excluded).unwrap();
+        assert_eq!(exclude.subnet_id, vec![0xe0]);
+        assert_eq!(exclude.prefix(delegated), Some(excluded));
+
+        // within an IAPrefix
+        let mut prefix = IAPrefix::new(delegated, 100, 200);
+        assert_eq!(prefix.prefix(), Some(delegated));
+        assert_eq!(prefix.excluded(), None);
+        prefix.opts.insert(DhcpOption::PdExclude(exclude));
+        assert_eq!(prefix.excluded(), Some(excluded));
+        test_opt(
+            DhcpOption::IAPrefix(prefix),
+            vec![
+                0, 26, 0, 31, 0, 0, 0, 100, 0, 0, 0, 200, 56, 0x20, 0x01, 0x0d, 0xb8, 0, 0, 0xab,
+                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 67, 0, 2, 59, 0xe0,
+            ],
+        )?;
+
+        // must be a longer prefix inside the delegated one
+        assert!(PdExclude::new(delegated, "2001:db8::/48".parse()?).is_none());
+        assert!(PdExclude::new(delegated, "2001:db8:0:cd00::/64".parse()?).is_none());
+        let bad = PdExclude {
+            prefix_len: 64,
+            subnet_id: vec![1, 2],
+        };
+        assert_eq!(bad.prefix(delegated), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ntp_server() -> Result<()> {
+        let mut bytes = vec![0, 56, 0, 49, 0, 1, 0, 16, 0x20, 0x01, 0x0d, 0xb8];
+        bytes.extend([0; 11]);
+        bytes.push(1);
+        bytes.extend([0, 2, 0, 16, 0xff, 0x05]);
+        bytes.extend([0; 12]);
+        bytes.extend([0x01, 0x01]);
+        bytes.extend([0, 3, 0, 5, 3, b'n', b't', b'p', 0]);
+        test_opt(
+            DhcpOption::NtpServer(vec![
+                NtpSuboption::ServerAddr("2001:db8::1".parse()?),
+                NtpSuboption::MulticastAddr("ff05::101".parse()?),
+                NtpSuboption::ServerFqdn(Domain(Name::from_str("ntp.")?)),
+            ]),
+            bytes,
+        )?;
+        test_opt(
+            DhcpOption::NtpServer(vec![NtpSuboption::Unknown {
+                code: 9,
+                data: vec![1, 2],
+            }]),
+            vec![0, 56, 0, 6, 0, 9, 0, 2, 1, 2],
+        )?;
+        test_opt(
+            DhcpOption::SntpServers(vec!["2001:db8::1".parse()?]),
+            vec![
+                0, 31, 0, 16, 0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+            ],
+        )?;
+        // sub-option longer than the option
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 56, 0, 4, 0, 1, 0, 16])).is_err());
+        // address sub-options must be 16 bytes
+        let mut bytes = vec![0, 56, 0, 21, 0, 1, 0, 17];
+        bytes.extend([0; 17]);
+        let err = DhcpOption::decode(&mut Decoder::new(&bytes)).unwrap_err();
+        assert_eq!(err.path().unwrap().to_string(), "[NtpServer].addr");
+        let mut bytes = vec![0, 56, 0, 12, 0, 2, 0, 8];
+        bytes.extend([0; 8]);
+        assert!(DhcpOption::decode(&mut Decoder::new(&bytes)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_relay_opts() -> Result<()> {
+        test_opt(
+            DhcpOption::RemoteId(RemoteId {
+                enterprise: 3561,
+                id: b"port-1".to_vec(),
+            }),
+            vec![
+                0, 37, 0, 10, 0, 0, 0x0d, 0xe9, b'p', b'o', b'r', b't', b'-', b'1',
+            ],
+        )?;
+        test_opt(
+            DhcpOption::SubscriberId(b"sub".to_vec()),
+            vec![0, 38, 0, 3, b's', b'u', b'b'],
+        )?;
+        test_opt(
+            DhcpOption::ERO(vec![OptionCode::InterfaceId, OptionCode::RemoteId]),
+            vec![0, 43, 0, 4, 0, 18, 0, 37],
+        )?;
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::InterfaceId(b"eth0".to_vec()));
+        test_opt(
+            DhcpOption::Rsoo(opts),
+            vec![0, 66, 0, 8, 0, 18, 0, 4, b'e', b't', b'h', b'0'],
+        )?;
+        test_opt(DhcpOption::RelayPort(5547), vec![0, 135, 0, 2, 0x15, 0xab])?;
+
+        // remote-id needs an enterprise number, ERO codes are 2 bytes
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 37, 0, 2, 0, 0])).is_err());
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 43, 0, 3, 0, 18, 0])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_linklayer_addr() -> Result<()> {
+        let mac: v4::HardwareAddr = "de:ad:be:ef:00:01".parse()?;
+        let opt = ClientLinklayerAddr::new(&mac);
+        assert_eq!(opt.hw_addr(), Some(mac));
+        test_opt(
+            DhcpOption::ClientLinklayerAddr(opt),
+            vec![0, 79, 0, 8, 0, 1, 0xde, 0xad, 0xbe, 0xef, 0, 1],
+        )?;
+
+        // wrong length for ethernet, kept but not a valid hw addr
+        let opt = ClientLinklayerAddr {
+            htype: 1,
+            addr: vec![1, 2, 3],
+        };
+        assert_eq!(opt.hw_type(), Some(v4::HType::Eth));
+        assert_eq!(opt.hw_addr(), None);
+        test_opt(
+            DhcpOption::ClientLinklayerAddr(opt),
+            vec![0, 79, 0, 5, 0, 1, 1, 2, 3],
+        )?;
+        // hardware types above 255 still decode
+        let opt = ClientLinklayerAddr {
+            htype: 256,
+            addr: vec![1, 2],
+        };
+        assert_eq!(opt.hw_type(), None);
+        assert_eq!(opt.hw_addr(), None);
+        test_opt(
+            DhcpOption::ClientLinklayerAddr(opt),
+            vec![0, 79, 0, 4, 1, 0, 1, 2],
+        )?;
+        // too short for the hardware type
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 79, 0, 1, 1])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_timer_opts() -> Result<()> {
+        test_opt(
+            DhcpOption::InformationRefreshTime(86400),
+            vec![0, 32, 0, 4, 0, 1, 0x51, 0x80],
+        )?;
+        test_opt(
+            DhcpOption::SolMaxRt(3600),
+            vec![0, 82, 0, 4, 0, 0, 0x0e, 0x10],
+        )?;
+        test_opt(DhcpOption::InfMaxRt(60), vec![0, 83, 0, 4, 0, 0, 0, 60])?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_boot_opts() -> Result<()> {
+        let mut bytes = vec![0, 59, 0, 25];
+        bytes.extend(b"tftp://[2001:db8::1]/boot");
+        test_opt(
+            DhcpOption::OptBootfileUrl("tftp://[2001:db8::1]/boot".parse()?),
+            bytes,
+        )?;
+        test_opt(
+            DhcpOption::OptBootfileParam(vec!["root=/dev/nfs".to_string(), String::new()]),
+            vec![
+                0, 60, 0, 17, 0, 13, b'r', b'o', b'o', b't', b'=', b'/', b'd', b'e', b'v', b'/',
+                b'n', b'f', b's', 0, 0,
+            ],
+        )?;
+        test_opt(
+            DhcpOption::ClientArchType(vec![
+                v4::Architecture::X86_64,
+                v4::Architecture::Unknown(0x10),
+            ]),
+            vec![0, 61, 0, 4, 0, 9, 0, 0x10],
+        )?;
+        test_opt(DhcpOption::Nii(1, 3, 10), vec![0, 62, 0, 3, 1, 3, 10])?;
+        // odd length arch list
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 61, 0, 3, 0, 9, 0])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_aftr_name() -> Result<()> {
+        test_opt(
+            DhcpOption::AftrName(Domain(Name::from_str("aftr.example.com.")?)),
+            vec![
+                0, 64, 0, 18, 4, b'a', b'f', b't', b'r', 7, b'e', b'x', b'a', b'm', b'p', b'l',
+                b'e', 3, b'c', b'o', b'm', 0,
+            ],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_s46_opts() -> Result<()> {
+        // BMR carrying port params
+        let mut rule = S46Rule::new("192.0.2.0/24".parse()?, "2001:db8::/40".parse()?, 16);
+        rule.fmr = true;
+        rule.set_port_params(S46PortParams::new(6, 0, 0));
+        let mut bytes = vec![
+            0, 89, 0, 21, 1, 16, 24, 192, 0, 2, 0, 40, 0x20, 0x01, 0x0d, 0xb8, 0,
+        ];
+        bytes.extend([0, 93, 0, 4, 6, 0, 0, 0]);
+        test_opt(DhcpOption::S46Rule(rule.clone()), bytes)?;
+        test_opt(
+            DhcpOption::S46Dmr("2001:db8:ffff::/64".parse()?),
+            vec![0, 91, 0, 9, 64, 0x20, 0x01, 0x0d, 0xb8, 0xff, 0xff, 0, 0],
+        )?;
+        // host bits of the prefixes aren't sent
+        let mut buf = Vec::new();
+        let mut e = Encoder::new(&mut buf);
+        DhcpOption::S46Rule(S46Rule::new(
+            "192.0.2.5/24".parse()?,
+            "2001:db8:ffff::1/36".parse()?,
+            16,
+        ))
+        .encode(&mut e)?;
+        assert_eq!(
+            buf,
+            [0, 89, 0, 13, 0, 16, 24, 192, 0, 2, 0, 36, 0x20, 0x01, 0x0d, 0xb8, 0xf0]
+        );
+
+        let mut bind_opts = DhcpOptions::new();
+        bind_opts.insert(DhcpOption::S46Portparams(S46PortParams::new(0, 6, 3)));
+        let bind = S46V4v6Bind {
+            ipv4_addr: "192.0.2.1".parse()?,
+            bind_prefix: "2001:db8:1::/56".parse()?,
+            opts: bind_opts,
+        };
+        let mut bytes = vec![
+            0, 92, 0, 20, 192, 0, 2, 1, 56, 0x20, 0x01, 0x0d, 0xb8, 0, 1, 0,
+        ];
+        bytes.extend([0, 93, 0, 4, 0, 6, 0x0c, 0]);
+        test_opt(DhcpOption::S46V4v6bind(bind.clone()), bytes)?;
+        assert_eq!(bind.port_set()?.len(), 1024);
+
+        // MAP-E, MAP-T & lw4o6 containers
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::S46Rule(rule));
+        opts.insert(DhcpOption::S46Br("2001:db8::1".parse()?));
+        for cont in [
+            DhcpOption::S46ContMape(opts.clone()),
+            DhcpOption::S46ContMapt(opts.clone()),
+            DhcpOption::S46ContLw(opts),
+        ] {
+            let mut buf = Vec::new();
+            cont.encode(&mut Encoder::new(&mut buf))?;
+            assert_eq!(DhcpOption::decode(&mut Decoder::new(&buf))?, cont);
+        }
+
+        // prefix length out of range
+        assert!(DhcpOption::decode(&mut Decoder::new(&[0, 91, 0, 1, 129])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_failover_opts() -> Result<()> {
+        test_opt(
+            DhcpOption::FBindingStatus(BindingStatus::Active),
+            vec![0, 114, 0, 1, 1],
+        )?;
+        test_opt(
+            DhcpOption::FServerState(ServerState::Unknown(200)),
+            vec![0, 132, 0, 1, 200],
+        )?;
+        test_opt(DhcpOption::FConnectFlags(1), vec![0, 115, 0, 2, 0, 1])?;
+        test_opt(
+            DhcpOption::FMclt(3600),
+            vec![0, 122, 0, 4, 0, 0, 0x0e, 0x10],
+        )?;
+        test_opt(
+            DhcpOption::FRelationshipName("p1".into()),
+            vec![0, 130, 0, 2, b'p', b'1'],
+        )?;
+        test_opt(
+            DhcpOption::FReconfigureData(ReconfigureData {
+                time: 1,
+                key: vec![0xaa, 0xbb],
+            }),
+            vec![0, 129, 0, 6, 0, 0, 0, 1, 0xaa, 0xbb],
+        )?;
+        let mut info = DhcpOptions::new();
+        info.insert(DhcpOption::FDNSHostName(Domain(Name::from_str("host.")?)));
+        info.insert(DhcpOption::Fdnsflags(3));
+        test_opt(
+            DhcpOption::Fdnsremovalinfo(info),
+            vec![
+                0, 116, 0, 16, 0, 117, 0, 6, 4, b'h', b'o', b's', b't', 0, 0, 119, 0, 2, 0, 3,
+            ],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_order() -> Result<()> {
+        // options with the same code keep the order they were added in
+        let mut opts = DhcpOptions::new();
+        opts.insert(DhcpOption::Preference(1));
+        opts.insert(DhcpOption::ClientId(vec![1]));
+        opts.insert(DhcpOption::ClientId(vec![2]));
+        opts.insert(DhcpOption::ServerId(vec![3]));
+        opts.insert(DhcpOption::ClientId(vec![4]));
+        assert_eq!(
+            opts.get_all(OptionCode::ClientId),
+            Some(
+                &[
+                    DhcpOption::ClientId(vec![1]),
+                    DhcpOption::ClientId(vec![2]),
+                    DhcpOption::ClientId(vec![4])
+                ][..]
+            )
+        );
+        let mut buf = Vec::new();
+        opts.encode(&mut Encoder::new(&mut buf))?;
+        assert_eq!(DhcpOptions::decode(&mut Decoder::new(&buf))?, opts);
+
+        // enough interleaved options that an unstable sort would reorder them,
+        // both when decoding and collecting
+        let interleaved = (0..40u8)
+            .map(|i| match i % 2 {
+                0 => DhcpOption::ServerId(vec![i]),
+                _ => DhcpOption::ClientId(vec![i]),
+            })
+            .collect::<Vec<_>>();
+        let mut buf = Vec::new();
+        let mut e = Encoder::new(&mut buf);
+        for opt in &interleaved {
+            opt.encode(&mut e)?;
+        }
+        let client_ids = interleaved
+            .iter()
+            .filter(|opt| matches!(opt, DhcpOption::ClientId(_)))
+            .cloned()
+            .collect::<Vec<_>>();
+        let decoded = DhcpOptions::decode(&mut Decoder::new(&buf))?;
+        assert_eq!(decoded.get_all(OptionCode::ClientId), Some(&client_ids[..]));
+        let collected = interleaved.into_iter().collect::<DhcpOptions>();
+        assert_eq!(
+            collected.get_all(OptionCode::ClientId),
+            Some(&client_ids[..])
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_range_binsearch() {
         let arr = vec![0, 1, 1, 1, 1, 4, 6, 7, 9, 9, 10];
@@ -902,5 +2237,27 @@ mod tests {
 
         let arr = vec![1, 2, 2, 2, 2, 3, 4, 7, 8, 8];
         assert_eq!(Some(7..=7), range_binsearch(&arr, |x| x.cmp(&7)));
+        // not found, or empty
+        let arr = vec![1, 2, 2, 2, 2, 3, 4, 7, 8, 8];
+        assert_eq!(None, range_binsearch(&arr, |x| x.cmp(&0)));
+        assert_eq!(None, first(&arr, |x| x.cmp(&0)));
+        assert_eq!(None, last(&arr, |x| x.cmp(&0)));
+        let arr: Vec<u8> = vec![];
+        assert_eq!(None, range_binsearch(&arr, |x| x.cmp(&0)));
+        assert_eq!(None, first(&arr, |x| x.cmp(&0)));
+        assert_eq!(None, last(&arr, |x| x.cmp(&0)));
+        assert_eq!(None, DhcpOptions::new().get(OptionCode::ClientId));
+    }
+
+    #[test]
+    fn test_last_in_bounds() {
+        // `last` must not look past the end of the slice it's given
+        let arr = [1, 2, 8, 8, 99];
+        let cmp = |x: &i32| {
+            assert_ne!(*x, 99, "read past the end of the slice");
+            x.cmp(&8)
+        };
+        assert_eq!(Some(3), last(&arr[..4], cmp));
+        assert_eq!(Some(2..=3), range_binsearch(&arr[..4], cmp));
     }
 }
@@ -0,0 +1,173 @@
+//! Prefix delegation helpers for a requesting router ([RFC 8415], [RFC 6603])
+//!
+//! [`DelegatedPrefix::from_message`] reads the prefixes delegated in a
+//! `REPLY`, and [`assign_subnets`] carves them into /64s for the router's
+//! downstream interfaces, skipping any prefix excluded with [`PdExclude`].
+//!
+//! ```rust
+//! use dhcproto::v6::{pd::{assign_subnets, DelegatedPrefix}, DhcpOption, IAPrefix, Message, MessageType, IAPD};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut reply = Message::new(MessageType::Reply);
+//! let mut iapd = IAPD { id: 1, t1: 1800, t2: 2880, opts: Default::default() };
+//! iapd.opts.insert(DhcpOption::IAPrefix(IAPrefix::new("2001:db8:0:a0::/60".parse()?, 3600, 7200)));
+//! reply.opts_mut().insert(DhcpOption::IAPD(iapd));
+//!
+//! let prefixes = DelegatedPrefix::from_message(&reply);
+//! assert_eq!(prefixes[0].valid_lifetime, 7200);
+//! assert_eq!(
+//!     assign_subnets(&prefixes, 2),
+//!     vec!["2001:db8:0:a0::/64".parse()?, "2001:db8:0:a1::/64".parse()?]
+//! );
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 8415]: https://datatracker.ietf.org/doc/html/rfc8415
+//! [RFC 6603]: https://datatracker.ietf.org/doc/html/rfc6603
+//! [`PdExclude`]: crate::v6::PdExclude
+use ipnet::Ipv6Net;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::v6::{DhcpOption, Message, OptionCode};
+
+/// A prefix delegated to the requesting router
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DelegatedPrefix {
+    /// IAID of the IA_PD holding the prefix
+    pub iaid: u32,
+    /// the delegated prefix
+    pub prefix: Ipv6Net,
+    /// preferred lifetime in seconds
+    pub preferred_lifetime: u32,
+    /// valid lifetime in seconds, 0 if the prefix was withdrawn
+    pub valid_lifetime: u32,
+    /// prefix within `prefix` the router must not use downstream
+    pub excluded: Option<Ipv6Net>,
+}
+
+impl DelegatedPrefix {
+    /// every prefix delegated in the IA_PD options of `msg`. Prefixes with
+    /// an invalid length are skipped
+    pub fn from_message(msg: &Message) -> Vec<Self> {
+        msg.opts()
+            .get_all(OptionCode::IAPD)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|opt| match opt {
+                DhcpOption::IAPD(iapd) => Some(iapd),
+                _ => None,
+            })
+            .flat_map(|iapd| {
+                iapd.prefixes().filter_map(move |prefix| {
+                    Some(DelegatedPrefix {
+                        iaid: iapd.id,
+                        prefix: prefix.prefix()?,
+                        preferred_lifetime: prefix.preferred_lifetime,
+                        valid_lifetime: prefix.valid_lifetime,
+                        excluded: prefix.excluded(),
+                    })
+                })
+            })
+            .collect()
+    }
+    /// the /64s of this prefix in order, without any that overlap the
+    /// excluded prefix. Empty if the prefix is longer than /64
+    pub fn subnets(&self) -> impl Iterator<Item = Ipv6Net> + '_ {
+        self.prefix
+            .subnets(64)
+            .into_iter()
+            .flatten()
+            .filter(move |net| match &self.excluded {
+                Some(excluded) => !net.contains(excluded) && !excluded.contains(net),
+                None => true,
+            })
+    }
+}
+
+/// carve up to `count` /64s for downstream interfaces from `prefixes`, in
+/// order. Prefixes whose valid lifetime is 0 are skipped. The same prefixes
+/// always give the same /64s, so interfaces keep their subnet across renews
+pub fn assign_subnets(prefixes: &[DelegatedPrefix], count: usize) -> Vec<Ipv6Net> {
+    prefixes
+        .iter()
+        .filter(|prefix| prefix.valid_lifetime != 0)
+        .flat_map(|prefix| prefix.subnets())
+        .take(count)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v6::{DhcpOptions, IAPrefix, MessageType, PdExclude, IAPD};
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    fn iapd(id: u32, prefixes: Vec<IAPrefix>) -> DhcpOption {
+        let mut opts = DhcpOptions::new();
+        for prefix in prefixes {
+            opts.insert(DhcpOption::IAPrefix(prefix));
+        }
+        DhcpOption::IAPD(IAPD {
+            id,
+            t1: 0,
+            t2: 0,
+            opts,
+        })
+    }
+
+    #[test]
+    fn test_delegated_prefixes() -> Result<()> {
+        let first: Ipv6Net = "2001:db8:1::/62".parse()?;
+        let excluded: Ipv6Net = "2001:db8:1:1::/64".parse()?;
+        let mut with_exclude = IAPrefix::new(first, 100, 200);
+        with_exclude.opts.insert(DhcpOption::PdExclude(
+            PdExclude::new(first, excluded).unwrap(),
+        ));
+        let withdrawn = IAPrefix::new("2001:db8:2::/64".parse()?, 0, 0);
+        let second = IAPrefix::new("2001:db8:3::/63".parse()?, 100, 200);
+        let mut bad = second.clone();
+        bad.prefix_len = 129;
+
+        let mut msg = Message::new(MessageType::Reply);
+        msg.opts_mut()
+            .insert(iapd(1, vec![with_exclude, withdrawn]));
+        msg.opts_mut().insert(iapd(2, vec![second, bad]));
+
+        let prefixes = DelegatedPrefix::from_message(&msg);
+        assert_eq!(prefixes.len(), 3);
+        assert_eq!(prefixes[0].iaid, 1);
+        assert_eq!(prefixes[0].excluded, Some(excluded));
+        assert_eq!(prefixes[2].iaid, 2);
+        assert_eq!(prefixes[0].subnets().count(), 3);
+
+        let subnets = assign_subnets(&prefixes, 5);
+        let expected: Vec<Ipv6Net> = [
+            "2001:db8:1::/64",
+            "2001:db8:1:2::/64",
+            "2001:db8:1:3::/64",
+            "2001:db8:3::/64",
+            "2001:db8:3:1::/64",
+        ]
+        .iter()
+        .map(|net| net.parse())
+        .collect::<std::result::Result<_, _>>()?;
+        assert_eq!(subnets, expected);
+        assert_eq!(assign_subnets(&prefixes, 2), expected[..2]);
+        assert_eq!(assign_subnets(&prefixes, 10).len(), 5);
+
+        // longer than /64 can't be carved
+        let long = DelegatedPrefix {
+            iaid: 0,
+            prefix: "2001:db8::/80".parse()?,
+            preferred_lifetime: 1,
+            valid_lifetime: 1,
+            excluded: None,
+        };
+        assert!(assign_subnets(&[long], 1).is_empty());
+        assert!(DelegatedPrefix::from_message(&Message::new(MessageType::Reply)).is_empty());
+        Ok(())
+    }
+}
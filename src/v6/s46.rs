@@ -0,0 +1,473 @@
+//! Softwire 46 options ([RFC 7598]) for MAP-E, MAP-T & lw4o6
+//!
+//! A MAP CE gets its IPv4 address & the ports it may use from a
+//! [`S46Rule`] and its delegated IPv6 prefix, see [`S46Rule::map`]. The
+//! computation follows [RFC 7597].
+//!
+//! ```rust
+//! use dhcproto::v6::s46::{S46PortParams, S46Rule};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // RFC 7597 appendix A, example 1
+//! let mut rule = S46Rule::new("192.0.2.0/24".parse()?, "2001:db8::/40".parse()?, 16);
+//! rule.set_port_params(S46PortParams::new(6, 0, 0));
+//! let ce = rule.map("2001:db8:12:3400::/56".parse()?)?;
+//! assert_eq!(ce.ipv4.addr(), "192.0.2.18".parse::<std::net::Ipv4Addr>()?);
+//! assert_eq!(ce.ports.psid(), 0x34);
+//! assert_eq!(ce.ports.ranges().next(), Some(1232..=1235));
+//! assert_eq!(ce.ipv6, "2001:db8:12:3400:0:c000:212:34".parse::<std::net::Ipv6Addr>()?);
+//! # Ok(()) }
+//! ```
+//!
+//! [RFC 7598]: https://datatracker.ietf.org/doc/html/rfc7598
+//! [RFC 7597]: https://datatracker.ietf.org/doc/html/rfc7597
+use std::{
+    net::{Ipv4Addr, Ipv6Addr},
+    ops::RangeInclusive,
+};
+
+use ipnet::{Ipv4Net, Ipv6Net};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    decoder::{Decodable, Decoder},
+    encoder::Encoder,
+    error::{DecodeError, DecodeResult, EncodeResult},
+    v6::{DhcpOption, DhcpOptions, OptionCode},
+};
+
+/// the PSID offset used when a rule has no port parameters
+pub const DEFAULT_PSID_OFFSET: u8 = 6;
+
+/// S46 Rule (89), a MAP basic or forwarding mapping rule
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S46Rule {
+    /// F flag, the rule is also a forwarding mapping rule
+    pub fmr: bool,
+    /// length of the embedded address bits in the delegated prefix
+    pub ea_len: u8,
+    /// rule IPv4 prefix
+    pub ipv4_prefix: Ipv4Net,
+    /// rule IPv6 prefix
+    pub ipv6_prefix: Ipv6Net,
+    /// S46 Port Parameters (93)
+    pub opts: DhcpOptions,
+}
+
+impl S46Rule {
+    /// a basic mapping rule with no options
+    pub fn new(ipv4_prefix: Ipv4Net, ipv6_prefix: Ipv6Net, ea_len: u8) -> Self {
+        Self {
+            fmr: false,
+            ea_len,
+            ipv4_prefix,
+            ipv6_prefix,
+            opts: DhcpOptions::new(),
+        }
+    }
+    /// the rule's port parameters, if any
+    pub fn port_params(&self) -> Option<&S46PortParams> {
+        match self.opts.get(OptionCode::S46Portparams)? {
+            DhcpOption::S46Portparams(params) => Some(params),
+            _ => None,
+        }
+    }
+    /// set the rule's port parameters
+    pub fn set_port_params(&mut self, params: S46PortParams) -> &mut Self {
+        self.opts.remove(OptionCode::S46Portparams);
+        self.opts.insert(DhcpOption::S46Portparams(params));
+        self
+    }
+    /// compute the IPv4 address/prefix, port set & MAP IPv6 address of a CE
+    /// with the end-user prefix `delegated`
+    pub fn map(&self, delegated: Ipv6Net) -> Result<MapEndpoint, MapError> {
+        let r = self.ipv4_prefix.prefix_len() as u32;
+        let o = self.ea_len as u32;
+        let rule6_len = self.ipv6_prefix.prefix_len() as u32;
+        if !self.ipv6_prefix.contains(&delegated) {
+            return Err(MapError::NotInRule);
+        }
+        if (delegated.prefix_len() as u32) < rule6_len + o {
+            return Err(MapError::PrefixTooShort);
+        }
+        if r + o > 48 {
+            return Err(MapError::InvalidEaLen(self.ea_len));
+        }
+        // the o bits following the rule prefix
+        let ea = match o {
+            0 => 0,
+            o => ((u128::from(delegated.network()) << rule6_len) >> (128 - o)) as u64,
+        };
+        let prefix4 = u32::from(self.ipv4_prefix.network());
+        let params = self.port_params();
+        let offset = params.map_or(DEFAULT_PSID_OFFSET, |p| p.offset);
+        let (ipv4, psid_len, psid) = if r + o <= 32 {
+            // a full address or prefix, the psid can only come from the
+            // port parameters (ex. MAP 1:1 with ea_len 0)
+            let len = r + o;
+            let addr = prefix4 | (ea as u32).checked_shl(32 - len).unwrap_or(0);
+            let (psid_len, psid) = params.map_or((0, 0), |p| (p.psid_len, p.psid()));
+            (
+                Ipv4Net::new(addr.into(), len as u8).unwrap(),
+                psid_len,
+                psid,
+            )
+        } else {
+            let k = r + o - 32;
+            let suffix = (ea >> k) as u32;
+            let psid = (ea & ((1 << k) - 1)) as u16;
+            (
+                Ipv4Net::new((prefix4 | suffix).into(), 32).unwrap(),
+                k as u8,
+                psid,
+            )
+        };
+        let ports = PortSet::new(offset, psid_len, psid)?;
+
+        // interface id: 16 bits 0, the IPv4 address, then the PSID
+        let iid = (u32::from(ipv4.addr()) as u128) << 16 | psid as u128;
+        let upper = u128::from(delegated.network()) & !((1u128 << 64) - 1);
+        Ok(MapEndpoint {
+            ipv4,
+            ports,
+            ipv6: Ipv6Addr::from(upper | iid),
+        })
+    }
+}
+
+impl Decodable for S46Rule {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(S46Rule {
+            fmr: decoder.field("flags", |d| d.read_u8())? & 0x01 == 1,
+            ea_len: decoder.field("ea_len", |d| d.read_u8())?,
+            ipv4_prefix: decoder.field("ipv4_prefix", |d| {
+                let len = d.read_u8()?;
+                let addr = d.read_ipv4(4)?;
+                Ipv4Net::new(addr, len).map_err(|_| bad_prefix_len(len))
+            })?,
+            ipv6_prefix: decoder.field("ipv6_prefix", read_prefix6)?,
+            opts: decoder.field("opts", DhcpOptions::decode)?,
+        })
+    }
+}
+
+/// S46 IPv4/IPv6 Address Binding (92), a lw4o6 binding
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S46V4v6Bind {
+    /// the full or shared IPv4 address of the CE
+    pub ipv4_addr: Ipv4Addr,
+    /// prefix the CE's lw4o6 IPv6 address is taken from
+    pub bind_prefix: Ipv6Net,
+    /// S46 Port Parameters (93)
+    pub opts: DhcpOptions,
+}
+
+impl S46V4v6Bind {
+    /// the binding's port parameters, if any
+    pub fn port_params(&self) -> Option<&S46PortParams> {
+        match self.opts.get(OptionCode::S46Portparams)? {
+            DhcpOption::S46Portparams(params) => Some(params),
+            _ => None,
+        }
+    }
+    /// the ports the CE may use, every port without port parameters
+    pub fn port_set(&self) -> Result<PortSet, MapError> {
+        match self.port_params() {
+            Some(params) => PortSet::new(params.offset, params.psid_len, params.psid()),
+            None => PortSet::new(0, 0, 0),
+        }
+    }
+}
+
+impl Decodable for S46V4v6Bind {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(S46V4v6Bind {
+            ipv4_addr: decoder.field("ipv4_addr", |d| d.read_ipv4(4))?,
+            bind_prefix: decoder.field("bind_prefix", read_prefix6)?,
+            opts: decoder.field("opts", DhcpOptions::decode)?,
+        })
+    }
+}
+
+/// S46 Port Parameters (93)
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct S46PortParams {
+    /// PSID offset, the number of high bits excluded from the PSID
+    pub offset: u8,
+    /// PSID length
+    pub psid_len: u8,
+    /// PSID, left aligned as sent on the wire
+    pub psid_field: u16,
+}
+
+impl S46PortParams {
+    /// port parameters with a right aligned `psid`
+    pub fn new(offset: u8, psid_len: u8, psid: u16) -> Self {
+        let psid_field = match psid_len {
+            0 => 0,
+            len => psid << (16 - len.min(16) as u32),
+        };
+        Self {
+            offset,
+            psid_len,
+            psid_field,
+        }
+    }
+    /// the PSID, right aligned
+    pub fn psid(&self) -> u16 {
+        match self.psid_len {
+            0 => 0,
+            len => self.psid_field >> (16 - len.min(16) as u32),
+        }
+    }
+}
+
+impl Decodable for S46PortParams {
+    fn decode(decoder: &'_ mut Decoder<'_>) -> DecodeResult<Self> {
+        Ok(S46PortParams {
+            offset: decoder.field("offset", |d| d.read_u8())?,
+            psid_len: decoder.field("psid_len", |d| d.read_u8())?,
+            psid_field: decoder.field("psid", |d| d.read_u16())?,
+        })
+    }
+}
+
+/// A CE's addresses & ports from [`S46Rule::map`]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MapEndpoint {
+    /// IPv4 address, a prefix shorter than /32 if the CE gets several
+    pub ipv4: Ipv4Net,
+    /// ports the CE may use
+    pub ports: PortSet,
+    /// the CE's MAP IPv6 address
+    pub ipv6: Ipv6Addr,
+}
+
+/// The ports of a shared IPv4 address given to one CE ([RFC 7597 section 5.1])
+///
+/// [RFC 7597 section 5.1]: https://datatracker.ietf.org/doc/html/rfc7597#section-5.1
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "PortSetRepr")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PortSet {
+    offset: u8,
+    psid_len: u8,
+    psid: u16,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct PortSetRepr {
+    offset: u8,
+    psid_len: u8,
+    psid: u16,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PortSetRepr> for PortSet {
+    type Error = MapError;
+
+    fn try_from(repr: PortSetRepr) -> Result<Self, Self::Error> {
+        PortSet::new(repr.offset, repr.psid_len, repr.psid)
+    }
+}
+
+impl PortSet {
+    /// a port set, fails if the offset & PSID don't fit in 16 bits
+    pub fn new(offset: u8, psid_len: u8, psid: u16) -> Result<Self, MapError> {
+        if offset as u32 + psid_len as u32 > 16 || (psid_len < 16 && (psid as u32) >> psid_len != 0)
+        {
+            return Err(MapError::InvalidPortParams);
+        }
+        Ok(Self {
+            offset,
+            psid_len,
+            psid,
+        })
+    }
+    /// PSID offset
+    pub fn offset(&self) -> u8 {
+        self.offset
+    }
+    /// PSID length, 0 if the address isn't shared
+    pub fn psid_len(&self) -> u8 {
+        self.psid_len
+    }
+    /// PSID, right aligned
+    pub fn psid(&self) -> u16 {
+        self.psid
+    }
+    /// the contiguous port ranges in the set, in order. With a non-zero
+    /// offset the ports where the offset bits are 0 (0-1023 by default) are
+    /// excluded
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<u16>> {
+        let (a, k) = (self.offset as u32, self.psid_len as u32);
+        let m = 16 - a - k;
+        let first = if a > 0 && k > 0 { 1u32 } else { 0 };
+        let count = if k == 0 { 1 } else { 1u32 << a };
+        let psid = self.psid as u32;
+        (first..count).map(move |i| {
+            let start = if k == 0 {
+                0
+            } else {
+                (i << (16 - a)) | (psid << m)
+            };
+            let end = if k == 0 {
+                0xffff
+            } else {
+                start | ((1 << m) - 1)
+            };
+            start as u16..=end as u16
+        })
+    }
+    /// true if `port` is in the set
+    pub fn contains(&self, port: u16) -> bool {
+        self.ranges().any(|range| range.contains(&port))
+    }
+    /// number of ports in the set
+    pub fn len(&self) -> u32 {
+        self.ranges()
+            .map(|range| (*range.end() - *range.start()) as u32 + 1)
+            .sum()
+    }
+    /// true if the set has no ports
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Errors computing a CE's MAP addresses & ports
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapError {
+    /// the delegated prefix isn't within the rule's IPv6 prefix
+    #[error("delegated prefix is not within the rule's IPv6 prefix")]
+    NotInRule,
+    /// the delegated prefix doesn't hold all of the rule's EA bits
+    #[error("delegated prefix is shorter than the rule prefix & EA bits")]
+    PrefixTooShort,
+    /// the EA bits hold more than 16 bits of PSID
+    #[error("EA bits length {0} leaves a PSID longer than 16 bits")]
+    InvalidEaLen(u8),
+    /// the PSID offset & length are longer than 16 bits, or the PSID doesn't
+    /// fit its length
+    #[error("invalid PSID offset, length or value")]
+    InvalidPortParams,
+}
+
+fn bad_prefix_len(len: u8) -> DecodeError {
+    DecodeError::Unknown(format!("invalid prefix length {len}").into())
+}
+
+/// a prefix length then only the bytes of the prefix it covers
+pub(crate) fn read_prefix6(decoder: &mut Decoder<'_>) -> DecodeResult<Ipv6Net> {
+    let len = decoder.read_u8()?;
+    if len > 128 {
+        return Err(bad_prefix_len(len));
+    }
+    let mut addr = [0; 16];
+    let sig_bytes = (len as usize + 7) / 8;
+    addr[..sig_bytes].copy_from_slice(decoder.read_slice(sig_bytes)?);
+    Ok(Ipv6Net::new(addr.into(), len).unwrap())
+}
+
+/// write a prefix as [`read_prefix6`] reads it
+pub(crate) fn write_prefix6(e: &mut Encoder<'_>, prefix: &Ipv6Net) -> EncodeResult<()> {
+    let sig_bytes = (prefix.prefix_len() as usize + 7) / 8;
+    e.write_u8(prefix.prefix_len())?;
+    e.write_slice(&prefix.network().octets()[..sig_bytes])
+}
+
+/// encoded length of a prefix written by [`write_prefix6`]
+pub(crate) fn prefix6_len(prefix: &Ipv6Net) -> u16 {
+    1 + (prefix.prefix_len() as u16 + 7) / 8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+    #[test]
+    fn test_map_rfc7597_examples() -> Result<()> {
+        // appendix A, example 2 & 3, same rule as the module docs
+        let mut rule = S46Rule::new("192.0.2.0/24".parse()?, "2001:db8::/40".parse()?, 16);
+        rule.fmr = true;
+        let ce = rule.map("2001:db8:12:3400::/56".parse()?)?;
+        assert_eq!(ce.ipv4, "192.0.2.18/32".parse()?);
+        assert_eq!(ce.ports.psid_len(), 8);
+        assert_eq!(ce.ports.psid(), 0x34);
+        let ranges: Vec<_> = ce.ports.ranges().collect();
+        assert_eq!(ranges.len(), 63);
+        assert_eq!(ranges[1], 2256..=2259);
+        assert_eq!(ranges[62], 64720..=64723);
+        assert_eq!(ce.ports.len(), 252);
+        assert!(ce.ports.contains(1233) && !ce.ports.contains(1236) && !ce.ports.contains(208));
+
+        assert_eq!(
+            ce.ipv6,
+            "2001:db8:12:3400:0:c000:212:34".parse::<Ipv6Addr>()?
+        );
+
+        // a prefix, not a shared address: /24 with 4 EA bits
+        let rule = S46Rule::new("192.0.2.0/24".parse()?, "2001:db8::/40".parse()?, 4);
+        let ce = rule.map("2001:db8:a0::/44".parse()?)?;
+        assert_eq!(ce.ipv4, "192.0.2.160/28".parse()?);
+        assert_eq!(ce.ports.len(), 65536);
+
+        // 1:1, full address in the rule & an explicit PSID
+        let mut rule = S46Rule::new("198.51.100.7/32".parse()?, "2001:db8:1::/48".parse()?, 0);
+        rule.set_port_params(S46PortParams::new(4, 4, 0xa));
+        let ce = rule.map("2001:db8:1::/48".parse()?)?;
+        assert_eq!(ce.ipv4, "198.51.100.7/32".parse()?);
+        assert_eq!(ce.ports.ranges().next(), Some(0x1a00..=0x1aff));
+        assert_eq!(ce.ports.len(), 15 * 256);
+
+        // errors
+        assert_eq!(
+            rule.map("2001:db8:2::/48".parse()?),
+            Err(MapError::NotInRule)
+        );
+        let rule = S46Rule::new("192.0.2.0/24".parse()?, "2001:db8::/40".parse()?, 16);
+        assert_eq!(
+            rule.map("2001:db8::/48".parse()?),
+            Err(MapError::PrefixTooShort)
+        );
+        let rule = S46Rule::new("192.0.2.0/24".parse()?, "2001:db8::/32".parse()?, 28);
+        assert_eq!(
+            rule.map("2001:db8::/64".parse()?),
+            Err(MapError::InvalidEaLen(28))
+        );
+        assert_eq!(PortSet::new(8, 9, 0), Err(MapError::InvalidPortParams));
+        assert_eq!(PortSet::new(6, 4, 16), Err(MapError::InvalidPortParams));
+        Ok(())
+    }
+
+    #[test]
+    fn test_port_params() {
+        let params = S46PortParams::new(6, 8, 0x34);
+        assert_eq!(params.psid_field, 0x3400);
+        assert_eq!(params.psid(), 0x34);
+        assert_eq!(S46PortParams::new(0, 0, 0).psid(), 0);
+        assert_eq!(S46PortParams::new(0, 16, 0xbeef).psid(), 0xbeef);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_port_set_json() -> Result<()> {
+        let ports = PortSet::new(6, 8, 0x34)?;
+        let s = serde_json::to_string(&ports)?;
+        assert_eq!(serde_json::from_str::<PortSet>(&s)?, ports);
+        // offset & PSID length longer than 16 bits
+        assert!(serde_json::from_str::<PortSet>(r#"{"offset":8,"psid_len":9,"psid":0}"#).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+//! Client timing parameters ([RFC 8415])
+//!
+//! [`ClientTimers`] holds the SOL_MAX_RT, INF_MAX_RT & information refresh
+//! time a client uses, starting from the RFC 8415 defaults. Servers change
+//! them with the SOL_MAX_RT (82), INF_MAX_RT (83) & Information Refresh Time
+//! (32) options, which [`ClientTimers::update`] applies from each `ADVERTISE`
+//! or `REPLY`.
+//!
+//! ```rust
+//! use dhcproto::v6::{timers::ClientTimers, DhcpOption, Message, MessageType};
+//! use std::time::Duration;
+//!
+//! let mut timers = ClientTimers::default();
+//! assert_eq!(timers.solicit().max_rt, Duration::from_secs(3600));
+//!
+//! let mut advertise = Message::new(MessageType::Advertise);
+//! advertise.opts_mut().insert(DhcpOption::SolMaxRt(600));
+//! assert!(timers.update(&advertise).is_empty());
+//! assert_eq!(timers.solicit().max_rt, Duration::from_secs(600));
+//! ```
+//!
+//! [RFC 8415]: https://datatracker.ietf.org/doc/html/rfc8415
+use std::{ops::RangeInclusive, time::Duration};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::v6::{binding::INFINITY, DhcpOption, Message, MessageType, OptionCode};
+
+/// initial Solicit timeout
+pub const SOL_INIT_RT: Duration = Duration::from_secs(1);
+/// default max Solicit timeout
+pub const SOL_MAX_RT: Duration = Duration::from_secs(3600);
+/// initial Information-request timeout
+pub const INF_INIT_RT: Duration = Duration::from_secs(1);
+/// default max Information-request timeout
+pub const INF_MAX_RT: Duration = Duration::from_secs(3600);
+/// information refresh time used when the server sends none
+pub const IRT_DEFAULT: Duration = Duration::from_secs(86400);
+/// shortest information refresh time a client accepts
+pub const IRT_MINIMUM: Duration = Duration::from_secs(600);
+/// SOL_MAX_RT & INF_MAX_RT option values a client accepts, in seconds
+pub const MAX_RT_RANGE: RangeInclusive<u32> = 60..=86400;
+
+/// Retransmission parameters of a message, see
+/// <https://datatracker.ietf.org/doc/html/rfc8415#section-15>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetransParams {
+    /// initial retransmission time (IRT)
+    pub initial: Duration,
+    /// max retransmission time (MRT)
+    pub max_rt: Duration,
+    /// max retransmission count (MRC), 0 for no limit
+    pub max_count: u32,
+    /// max retransmission duration (MRD), 0 for no limit
+    pub max_duration: Duration,
+}
+
+/// Timing parameters of a client that servers can update
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClientTimers {
+    /// max Solicit retransmission time
+    pub sol_max_rt: Duration,
+    /// max Information-request retransmission time
+    pub inf_max_rt: Duration,
+    /// time until a stateless client asks for its configuration again
+    pub information_refresh: Duration,
+}
+
+impl Default for ClientTimers {
+    fn default() -> Self {
+        Self {
+            sol_max_rt: SOL_MAX_RT,
+            inf_max_rt: INF_MAX_RT,
+            information_refresh: IRT_DEFAULT,
+        }
+    }
+}
+
+impl ClientTimers {
+    /// apply the timer options of an `ADVERTISE` or `REPLY`, other messages
+    /// are ignored. SOL_MAX_RT & INF_MAX_RT values outside [`MAX_RT_RANGE`]
+    /// are ignored, as RFC 8415 requires, and returned. The Information
+    /// Refresh Time is only read from a `REPLY`, see [`information_refresh_time`]
+    pub fn update(&mut self, msg: &Message) -> Vec<TimerError> {
+        let mut errors = Vec::new();
+        if !matches!(msg.msg_type(), MessageType::Advertise | MessageType::Reply) {
+            return errors;
+        }
+        if let Some(DhcpOption::SolMaxRt(secs)) = msg.opts().get(OptionCode::SolMaxRt) {
+            match max_rt(*secs) {
+                Some(d) => self.sol_max_rt = d,
+                None => errors.push(TimerError::SolMaxRt(*secs)),
+            }
+        }
+        if let Some(DhcpOption::InfMaxRt(secs)) = msg.opts().get(OptionCode::InfMaxRt) {
+            match max_rt(*secs) {
+                Some(d) => self.inf_max_rt = d,
+                None => errors.push(TimerError::InfMaxRt(*secs)),
+            }
+        }
+        if msg.msg_type() == MessageType::Reply
+            && msg.opts().get(OptionCode::InformationRefreshTime).is_some()
+        {
+            self.information_refresh = information_refresh_time(msg);
+        }
+        errors
+    }
+    /// retransmission parameters for `SOLICIT`
+    pub fn solicit(&self) -> RetransParams {
+        RetransParams {
+            initial: SOL_INIT_RT,
+            max_rt: self.sol_max_rt,
+            max_count: 0,
+            max_duration: Duration::ZERO,
+        }
+    }
+    /// retransmission parameters for `INFORMATION-REQUEST`
+    pub fn information_request(&self) -> RetransParams {
+        RetransParams {
+            initial: INF_INIT_RT,
+            max_rt: self.inf_max_rt,
+            max_count: 0,
+            max_duration: Duration::ZERO,
+        }
+    }
+}
+
+/// the information refresh time of a `REPLY` to an `INFORMATION-REQUEST`.
+/// [`IRT_DEFAULT`] if the server sent none, never less than [`IRT_MINIMUM`].
+/// 0xffffffff is [`INFINITY`]
+pub fn information_refresh_time(reply: &Message) -> Duration {
+    match reply.opts().get(OptionCode::InformationRefreshTime) {
+        Some(DhcpOption::InformationRefreshTime(u32::MAX)) => INFINITY,
+        Some(DhcpOption::InformationRefreshTime(secs)) => {
+            Duration::from_secs(*secs as u64).max(IRT_MINIMUM)
+        }
+        _ => IRT_DEFAULT,
+    }
+}
+
+fn max_rt(secs: u32) -> Option<Duration> {
+    MAX_RT_RANGE
+        .contains(&secs)
+        .then(|| Duration::from_secs(secs as u64))
+}
+
+/// Timer option values a client must ignore
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerError {
+    /// SOL_MAX_RT outside [`MAX_RT_RANGE`]
+    #[error("SOL_MAX_RT {0} is outside 60..=86400")]
+    SolMaxRt(u32),
+    /// INF_MAX_RT outside [`MAX_RT_RANGE`]
+    #[error("INF_MAX_RT {0} is outside 60..=86400")]
+    InfMaxRt(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg(msg_type: MessageType, opts: Vec<DhcpOption>) -> Message {
+        let mut msg = Message::new(msg_type);
+        for opt in opts {
+            msg.opts_mut().insert(opt);
+        }
+        msg
+    }
+
+    #[test]
+    fn test_update() {
+        let mut timers = ClientTimers::default();
+        let reply = msg(
+            MessageType::Reply,
+            vec![
+                DhcpOption::SolMaxRt(60),
+                DhcpOption::InfMaxRt(86400),
+                DhcpOption::InformationRefreshTime(7200),
+            ],
+        );
+        assert!(timers.update(&reply).is_empty());
+        assert_eq!(timers.solicit().max_rt, Duration::from_secs(60));
+        assert_eq!(
+            timers.information_request().max_rt,
+            Duration::from_secs(86400)
+        );
+        assert_eq!(timers.information_refresh, Duration::from_secs(7200));
+
+        // out of range values are ignored, the valid one is applied
+        let advertise = msg(
+            MessageType::Advertise,
+            vec![DhcpOption::SolMaxRt(59), DhcpOption::InfMaxRt(120)],
+        );
+        assert_eq!(timers.update(&advertise), vec![TimerError::SolMaxRt(59)]);
+        assert_eq!(timers.sol_max_rt, Duration::from_secs(60));
+        assert_eq!(timers.inf_max_rt, Duration::from_secs(120));
+        let reply = msg(MessageType::Reply, vec![DhcpOption::InfMaxRt(86401)]);
+        assert_eq!(timers.update(&reply), vec![TimerError::InfMaxRt(86401)]);
+
+        // only from an advertise or reply
+        let before = timers;
+        let renew = msg(
+            MessageType::Renew,
+            vec![
+                DhcpOption::SolMaxRt(300),
+                DhcpOption::InformationRefreshTime(900),
+            ],
+        );
+        assert!(timers.update(&renew).is_empty());
+        assert_eq!(timers, before);
+        let advertise = msg(
+            MessageType::Advertise,
+            vec![DhcpOption::InformationRefreshTime(900)],
+        );
+        timers.update(&advertise);
+        assert_eq!(timers, before);
+    }
+
+    #[test]
+    fn test_information_refresh_time() {
+        let irt = |secs| {
+            information_refresh_time(&msg(
+                MessageType::Reply,
+                vec![DhcpOption::InformationRefreshTime(secs)],
+            ))
+        };
+        assert_eq!(irt(60), IRT_MINIMUM);
+        assert_eq!(irt(3600), Duration::from_secs(3600));
+        assert_eq!(irt(u32::MAX), INFINITY);
+        assert_eq!(
+            information_refresh_time(&Message::new(MessageType::Reply)),
+            IRT_DEFAULT
+        );
+    }
+}